@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+const DEFAULT_CONFIG_PATH: &str = "assets/default_species_config.json";
+
+const USAGE: &str =
+    "usage: rust-ecosystem-simulator [--config <path>] [--scenario <path>] [--sweep <path>] [--optimize <path>]";
+
+/// What the program was started with.
+pub struct Arguments {
+    pub config_path: PathBuf,
+    pub scenario_path: Option<PathBuf>,
+    /// Runs the sweep in this file without opening a window.
+    pub sweep_path: Option<PathBuf>,
+    /// Runs the optimizer in this file without opening a window.
+    pub optimizer_path: Option<PathBuf>,
+}
+
+impl Arguments {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut arguments = Arguments {
+            config_path: PathBuf::from(DEFAULT_CONFIG_PATH),
+            scenario_path: None,
+            sweep_path: None,
+            optimizer_path: None,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("{} needs a value\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--config" => arguments.config_path = PathBuf::from(value()?),
+                "--scenario" => arguments.scenario_path = Some(PathBuf::from(value()?)),
+                "--sweep" => arguments.sweep_path = Some(PathBuf::from(value()?)),
+                "--optimize" => arguments.optimizer_path = Some(PathBuf::from(value()?)),
+                _ => return Err(format!("unknown argument {}\n{}", arg, USAGE)),
+            }
+        }
+        Ok(arguments)
+    }
+}
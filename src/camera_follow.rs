@@ -0,0 +1,97 @@
+use ggez::mint::Point2;
+
+use crate::organisms::organism_info::OrganismInfo;
+
+/// What the camera switches to when the followed organism disappears.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FollowOnDeath {
+    Stop,
+    Corpse,
+    Offspring,
+}
+
+impl FollowOnDeath {
+    pub fn next(self) -> Self {
+        match self {
+            FollowOnDeath::Stop => FollowOnDeath::Corpse,
+            FollowOnDeath::Corpse => FollowOnDeath::Offspring,
+            FollowOnDeath::Offspring => FollowOnDeath::Stop,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FollowOnDeath::Stop => "stop",
+            FollowOnDeath::Corpse => "corpse",
+            FollowOnDeath::Offspring => "offspring",
+        }
+    }
+}
+
+/// Keeps the camera locked onto an organism by id, so it survives snapshot updates.
+pub struct CameraFollow {
+    organism_id: u64,
+}
+
+impl CameraFollow {
+    pub fn new(organism_id: u64) -> Self {
+        Self { organism_id }
+    }
+
+    pub fn organism_id(&self) -> u64 {
+        self.organism_id
+    }
+
+    /// Finds the followed organism, switching to its successor if it's gone.
+    /// Returns None when there's nothing left to follow.
+    pub fn find_target<'a>(
+        &mut self,
+        organism_infos: &'a [OrganismInfo],
+        on_death: FollowOnDeath,
+    ) -> Option<&'a OrganismInfo> {
+        if let Some(info) = organism_infos
+            .iter()
+            .find(|info| info.id() == self.organism_id)
+        {
+            return Some(info);
+        }
+
+        let successor = Self::find_successor(self.organism_id, organism_infos, on_death)?;
+        self.organism_id = successor.id();
+        Some(successor)
+    }
+
+    fn find_successor(
+        id: u64,
+        organism_infos: &[OrganismInfo],
+        on_death: FollowOnDeath,
+    ) -> Option<&OrganismInfo> {
+        let mut descendants = organism_infos
+            .iter()
+            .filter(|info| info.parent_id() == Some(id));
+
+        match on_death {
+            FollowOnDeath::Stop => None,
+            FollowOnDeath::Corpse => descendants.find(|info| info.is_corpse()),
+            // the oldest child has the best chance of surviving
+            FollowOnDeath::Offspring => descendants
+                .filter(|info| !info.is_corpse())
+                .max_by_key(|info| info.shared_state().age()),
+        }
+    }
+
+    /// Moves `camera_position` towards `target` so that it closes
+    /// most of the distance within a fraction of a second.
+    pub fn smooth_towards(
+        camera_position: Point2<f32>,
+        target: Point2<f32>,
+        smoothness: f32,
+        delta_s: f32,
+    ) -> Point2<f32> {
+        let t = 1.0 - (-smoothness * delta_s).exp();
+        Point2 {
+            x: camera_position.x + (target.x - camera_position.x) * t,
+            y: camera_position.y + (target.y - camera_position.y) * t,
+        }
+    }
+}
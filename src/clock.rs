@@ -0,0 +1,116 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use crate::{
+    configurations::clock_configuration::ClockConfiguration, organisms::species::Activity,
+};
+
+const SEASON_NAMES: [&str; 4] = ["spring", "summer", "autumn", "winter"];
+
+/// Turns the simulated time into the time of day and year.
+pub struct Clock {
+    config: ClockConfiguration,
+}
+
+/// The conditions at one moment of the simulation.
+#[derive(Clone, Copy)]
+pub struct ClockReading {
+    /// From 0 at midnight over 0.5 at noon to 1 at the next midnight.
+    pub time_of_day: f32,
+    /// From 0 at the start of spring to 1 at the end of winter.
+    pub time_of_year: f32,
+    /// Which day it is, starting at 0.
+    pub day: u64,
+    /// How bright it is, from 0 to 1.
+    pub light: f32,
+    /// What plant growth and reproduction are multiplied by.
+    pub season_factor: f32,
+    rest_strength: f32,
+}
+
+impl Default for ClockReading {
+    /// Noon in spring, with the clock turned off.
+    fn default() -> Self {
+        Self {
+            time_of_day: 0.5,
+            time_of_year: 0.0,
+            day: 0,
+            light: 1.0,
+            season_factor: 1.0,
+            rest_strength: 0.0,
+        }
+    }
+}
+
+impl ClockReading {
+    pub fn is_day(&self) -> bool {
+        (0.25..0.75).contains(&self.time_of_day)
+    }
+
+    pub fn season_name(&self) -> &'static str {
+        SEASON_NAMES[((self.time_of_year * 4.0) as usize).min(3)]
+    }
+
+    /// The chance that an organism that is done idling does something else.
+    pub fn activity_chance(&self, activity: Activity) -> f32 {
+        let resting = match activity {
+            Activity::Always => false,
+            Activity::Diurnal => !self.is_day(),
+            Activity::Nocturnal => self.is_day(),
+        };
+        if resting {
+            1.0 - self.rest_strength
+        } else {
+            1.0
+        }
+    }
+
+    /// Like "day 3, 14:05, summer".
+    pub fn description(&self) -> String {
+        let minutes = (self.time_of_day * 24.0 * 60.0) as u32;
+        format!(
+            "day {}, {:02}:{:02}, {}",
+            self.day + 1,
+            minutes / 60,
+            minutes % 60,
+            self.season_name()
+        )
+    }
+}
+
+impl Clock {
+    pub fn new(config: &ClockConfiguration) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// The simulation starts at dawn on the first day of spring.
+    pub fn read(&self, time: Duration) -> ClockReading {
+        if !self.config.enabled {
+            return ClockReading::default();
+        }
+        let time = time.as_secs_f64();
+        let days = time / self.config.day_length.as_secs_f64() + 0.25;
+        let years = time / self.config.year_length.as_secs_f64();
+        let time_of_day = days.fract() as f32;
+        let time_of_year = years.fract() as f32;
+
+        // the sun is up half of the day and highest at noon
+        let sun = (-(TAU * time_of_day).cos()).max(0.0);
+        let night_light = self.config.night_light;
+        // highest in the middle of summer, lowest in the middle of winter
+        let season = (TAU * (time_of_year - 0.125)).sin();
+        ClockReading {
+            time_of_day,
+            time_of_year,
+            day: days as u64,
+            light: night_light + (1.0 - night_light) * sun,
+            season_factor: 1.0 + self.config.season_strength * season,
+            rest_strength: self.config.rest_strength,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The length of days and years, and how much they change.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockConfiguration {
+    /// Without the clock it's always a spring day with full light,
+    /// and every species is always active.
+    pub enabled: bool,
+    pub day_length: Duration,
+    pub year_length: Duration,
+    /// The light at midnight, as a fraction of the light at noon.
+    pub night_light: f32,
+    /// How much seasons change plant growth and reproduction, from 0 to 1.
+    /// Midsummer multiplies both by 1 + this, midwinter by 1 - this.
+    pub season_strength: f32,
+    /// How likely diurnal species stay idle at night and nocturnal ones at day
+    /// instead of doing something, from 0 to 1.
+    pub rest_strength: f32,
+}
+
+impl Default for ClockConfiguration {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            day_length: Duration::from_secs(120),
+            year_length: Duration::from_secs(960),
+            night_light: 0.05,
+            season_strength: 0.5,
+            rest_strength: 0.8,
+        }
+    }
+}
+
+impl ClockConfiguration {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.day_length.is_zero() || self.year_length.is_zero() {
+            return Err(String::from(
+                "day_length and year_length have to be above 0",
+            ));
+        }
+        let fractions = [
+            ("night_light", self.night_light),
+            ("season_strength", self.season_strength),
+            ("rest_strength", self.rest_strength),
+        ];
+        for (name, value) in fractions {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("clock {} has to be between 0 and 1", name));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What happens when the watched configuration file changes.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReloadMode {
+    /// Existing organisms take over the new parameters of their species.
+    ApplyLive,
+    Restart,
+    Off,
+}
+
+impl ReloadMode {
+    pub fn next(self) -> Self {
+        match self {
+            ReloadMode::ApplyLive => ReloadMode::Restart,
+            ReloadMode::Restart => ReloadMode::Off,
+            ReloadMode::Off => ReloadMode::ApplyLive,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReloadMode::ApplyLive => "apply live",
+            ReloadMode::Restart => "restart",
+            ReloadMode::Off => "off",
+        }
+    }
+}
+
+/// Notices changes to a file by polling its modification time,
+/// which also works for editors that save by replacing the file.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let last_modified = modified(&path);
+        Self {
+            path,
+            last_modified,
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true once for every change of the file, checking at most every [`POLL_INTERVAL`].
+    pub fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        let modified = modified(&self.path);
+        if modified == self.last_modified {
+            return false;
+        }
+        self.last_modified = modified;
+        // a deleted file isn't a new configuration
+        modified.is_some()
+    }
+
+    /// Forgets changes made so far, for when the file was written by the viewer itself.
+    pub fn mark_seen(&mut self) {
+        self.last_modified = modified(&self.path);
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
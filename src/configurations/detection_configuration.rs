@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation_event::SimulationEventKind;
+
+/// When the population series counts as collapsed, steady or cycling,
+/// and which of those stop a headless run.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionConfiguration {
+    /// How often the populations are counted for the series.
+    pub sample_interval: Duration,
+    /// How much of the series steady states and limit cycles are looked for in.
+    pub window: Duration,
+    /// The whole population collapsed when it's at or below this.
+    pub collapse_population: u32,
+    /// Populations are steady when their standard deviation in the window
+    /// is at most this fraction of their mean.
+    pub steady_state_tolerance: f32,
+    /// How similar the series has to be to itself shifted by the period to count as a cycle,
+    /// from 0 to 1.
+    pub cycle_correlation: f32,
+    pub stop_on_extinction: bool,
+    pub stop_on_collapse: bool,
+    pub stop_on_steady_state: bool,
+    pub stop_on_limit_cycle: bool,
+}
+
+impl Default for DetectionConfiguration {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_secs(1),
+            window: Duration::from_secs(60),
+            collapse_population: 0,
+            steady_state_tolerance: 0.05,
+            cycle_correlation: 0.6,
+            stop_on_extinction: false,
+            stop_on_collapse: false,
+            stop_on_steady_state: false,
+            stop_on_limit_cycle: false,
+        }
+    }
+}
+
+impl DetectionConfiguration {
+    /// How many samples fit in the window.
+    pub fn window_samples(&self) -> usize {
+        (self.window.as_secs_f64() / self.sample_interval.as_secs_f64()) as usize
+    }
+
+    /// Whether a headless run should stop because of an event of `kind`.
+    pub fn stops_on(&self, kind: &SimulationEventKind) -> bool {
+        match kind {
+            SimulationEventKind::Extinction { .. } => self.stop_on_extinction,
+            SimulationEventKind::Collapse => self.stop_on_collapse,
+            SimulationEventKind::SteadyState => self.stop_on_steady_state,
+            SimulationEventKind::LimitCycle { .. } => self.stop_on_limit_cycle,
+            // weather only changes the populations, it says nothing about them
+            SimulationEventKind::Rain { .. }
+            | SimulationEventKind::Drought { .. }
+            | SimulationEventKind::Fire { .. }
+            | SimulationEventKind::Flood { .. } => false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_interval.is_zero() {
+            return Err(String::from("sample_interval has to be above 0"));
+        }
+        if self.window_samples() < 8 {
+            return Err(String::from("window has to fit at least 8 samples"));
+        }
+        if !self.steady_state_tolerance.is_finite() || self.steady_state_tolerance < 0.0 {
+            return Err(String::from("steady_state_tolerance can't be negative"));
+        }
+        if !(0.0..=1.0).contains(&self.cycle_correlation) {
+            return Err(String::from("cycle_correlation has to be between 0 and 1"));
+        }
+        Ok(())
+    }
+}
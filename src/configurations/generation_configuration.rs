@@ -1,8 +1,113 @@
-use serde::{Serialize, Deserialize};
+use std::{collections::HashSet, fs, path::Path};
 
-use super::species_generation_configuration::SpeciesGenerationConfiguration;
+use serde::{Deserialize, Serialize};
+
+use crate::organisms::species::MAX_DURATION_S;
+
+use super::{
+    clock_configuration::ClockConfiguration,
+    nutrient_configuration::NutrientConfiguration,
+    species_generation_configuration::{SpeciesGenerationConfiguration, NUMERIC_PARAMETERS},
+    terrain_configuration::TerrainConfiguration,
+    weather_configuration::WeatherConfiguration,
+};
+
+/// Corpses are created with this species name, so configured species can't use it.
+const RESERVED_SPECIES_NAME: &str = "Corpse";
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct GenerationConfiguration {
     pub species: Vec<SpeciesGenerationConfiguration>,
+    #[serde(default)]
+    pub terrain: TerrainConfiguration,
+    #[serde(default)]
+    pub nutrients: NutrientConfiguration,
+    #[serde(default)]
+    pub clock: ClockConfiguration,
+    #[serde(default)]
+    pub weather: WeatherConfiguration,
+}
+
+impl GenerationConfiguration {
+    /// Reads and validates the configuration in `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|error| format!("reading {} failed: {}", path.display(), error))?;
+        let config: Self = serde_json::from_str(&json)
+            .map_err(|error| format!("parsing {} failed: {}", path.display(), error))?;
+        config
+            .validate()
+            .map_err(|error| format!("{} is invalid: {}", path.display(), error))?;
+        Ok(config)
+    }
+
+    /// Checks for values the simulation can't work with.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.species.is_empty() {
+            return Err(String::from("there are no species"));
+        }
+
+        let mut names = HashSet::new();
+        for species_config in self.species.iter() {
+            let species = &species_config.species;
+            if species.name.is_empty() {
+                return Err(String::from("a species has no name"));
+            }
+            if species.name == RESERVED_SPECIES_NAME {
+                return Err(format!("{} is reserved for corpses", RESERVED_SPECIES_NAME));
+            }
+            if !names.insert(species.name.as_str()) {
+                return Err(format!("{} is configured more than once", species.name));
+            }
+
+            for name in NUMERIC_PARAMETERS {
+                let value = species_config.parameter(name).unwrap_or(0.0);
+                if !value.is_finite() || value < 0.0 {
+                    return Err(format!("{} of {} can't be {}", name, species.name, value));
+                }
+            }
+            for (name, duration) in [
+                ("max_age", species.max_age),
+                ("birth_immunity", species.birth_immunity),
+            ] {
+                if duration.as_secs_f32() > MAX_DURATION_S {
+                    return Err(format!(
+                        "{} of {} can't be longer than {}s",
+                        name, species.name, MAX_DURATION_S
+                    ));
+                }
+            }
+            if species.max_energy <= 0.0 || species.max_health <= 0.0 {
+                return Err(format!(
+                    "{} needs a max_energy and max_health above 0",
+                    species.name
+                ));
+            }
+            let color = species.color;
+            if [color.r, color.g, color.b, color.a]
+                .iter()
+                .any(|channel| !(0.0..=1.0).contains(channel))
+            {
+                return Err(format!(
+                    "color of {} has to be between 0 and 1",
+                    species.name
+                ));
+            }
+        }
+        self.terrain.validate()?;
+        self.nutrients.validate()?;
+        self.clock.validate()?;
+        self.weather.validate()
+    }
+
+    /// Writes the configuration as indented json, the same way the bundled one is.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut json = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+        self.serialize(&mut serializer)
+            .map_err(|error| format!("serializing the configuration failed: {}", error))?;
+        fs::write(path, json)
+            .map_err(|error| format!("writing {} failed: {}", path.display(), error))
+    }
 }
@@ -1,2 +1,12 @@
+pub mod clock_configuration;
+pub mod config_watcher;
+pub mod detection_configuration;
 pub mod generation_configuration;
+pub mod nutrient_configuration;
+pub mod optimizer_configuration;
+pub mod parameter_bounds;
+pub mod scenario;
 pub mod species_generation_configuration;
+pub mod sweep_configuration;
+pub mod terrain_configuration;
+pub mod weather_configuration;
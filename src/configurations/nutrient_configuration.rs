@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// The nutrients in the soil, which plants need to photosynthesize
+/// and which dead organisms give back.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NutrientConfiguration {
+    /// Without nutrients, plants photosynthesize at their full rate everywhere.
+    pub enabled: bool,
+    /// Width and height of a cell of the nutrient field in world units.
+    pub cell_size: f32,
+    /// Nutrients per square meter at the start.
+    pub initial_density: f32,
+    /// Nutrients used up for every unit of energy gained by photosynthesis.
+    pub per_energy: f32,
+    /// Nutrients a corpse gives to the soil while it decays.
+    /// Scavengers eating it take the rest.
+    pub per_corpse: f32,
+    /// Nutrients a plant gives back when it dies of age.
+    pub per_dead_plant: f32,
+    /// Fraction of the difference to every neighbouring cell that moves over per second.
+    pub diffusion_rate_s: f32,
+}
+
+impl Default for NutrientConfiguration {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cell_size: 5.0,
+            initial_density: 2.0,
+            per_energy: 0.01,
+            per_corpse: 20.0,
+            per_dead_plant: 1.0,
+            diffusion_rate_s: 0.02,
+        }
+    }
+}
+
+impl NutrientConfiguration {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.cell_size.is_finite() && self.cell_size >= 0.5) {
+            return Err(String::from("nutrient cell_size has to be at least 0.5"));
+        }
+        let values = [
+            ("initial_density", self.initial_density),
+            ("per_energy", self.per_energy),
+            ("per_corpse", self.per_corpse),
+            ("per_dead_plant", self.per_dead_plant),
+            ("diffusion_rate_s", self.diffusion_rate_s),
+        ];
+        for (name, value) in values {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("nutrient {} can't be {}", name, value));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fs, path::Path, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    detection_configuration::DetectionConfiguration,
+    generation_configuration::GenerationConfiguration, parameter_bounds::ParameterBounds,
+    scenario::Scenario,
+};
+
+/// An evolutionary search for the parameters that keep every species alive the longest.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OptimizerConfiguration {
+    /// The [`GenerationConfiguration`] the parameters are changed in.
+    /// Its own values are one of the first candidates.
+    pub base_config: PathBuf,
+    pub parameters: Vec<ParameterBounds>,
+    /// How many candidates every generation has.
+    pub population_size: u32,
+    pub generations: u32,
+    /// How many of the best candidates get into the next generation unchanged.
+    pub elite: u32,
+    /// The standard deviation of mutations, as a fraction of the bounds of the parameter.
+    pub mutation_strength: f32,
+    /// How many differently seeded runs every candidate gets. Every candidate uses the same seeds.
+    pub runs_per_candidate: u32,
+    pub seed: u64,
+    /// How much time every run simulates at most.
+    pub duration: Duration,
+    pub time_step: Duration,
+    /// Where the best configurations are written.
+    pub output_directory: PathBuf,
+    /// How many of the best configurations are written.
+    pub best_count: u32,
+    /// What counts as a collapse, steady state or limit cycle.
+    /// Runs always stop at the first extinction.
+    #[serde(default)]
+    pub detection: DetectionConfiguration,
+    /// A [`Scenario`] played in every run, checked against the base configuration.
+    /// Its seed is ignored, the runs have their own.
+    #[serde(default)]
+    pub scenario: Option<PathBuf>,
+}
+
+impl OptimizerConfiguration {
+    /// Reads and validates the optimizer in `path`, returning it with its base configuration.
+    pub fn load(path: &Path) -> Result<(Self, GenerationConfiguration), String> {
+        let json = fs::read_to_string(path)
+            .map_err(|error| format!("reading {} failed: {}", path.display(), error))?;
+        let optimizer: Self = serde_json::from_str(&json)
+            .map_err(|error| format!("parsing {} failed: {}", path.display(), error))?;
+        let base_config = GenerationConfiguration::load(&optimizer.base_config)?;
+        optimizer
+            .validate(&base_config)
+            .map_err(|error| format!("{} is invalid: {}", path.display(), error))?;
+        Ok((optimizer, base_config))
+    }
+
+    /// The scenario every run plays, an empty one if there is none.
+    pub fn load_scenario(&self, base_config: &GenerationConfiguration) -> Result<Scenario, String> {
+        match &self.scenario {
+            Some(path) => Scenario::load(path, base_config),
+            None => Ok(Scenario::default()),
+        }
+    }
+
+    /// Checks that the parameters exist in `base_config` and the search can be made.
+    pub fn validate(&self, base_config: &GenerationConfiguration) -> Result<(), String> {
+        if self.parameters.is_empty() {
+            return Err(String::from("there are no parameters to optimize"));
+        }
+        for bounds in self.parameters.iter() {
+            bounds.validate(base_config)?;
+        }
+
+        if self.population_size < 2 {
+            return Err(String::from("population_size has to be at least 2"));
+        }
+        if self.elite >= self.population_size {
+            return Err(String::from("elite has to be below population_size"));
+        }
+        if self.generations == 0 || self.runs_per_candidate == 0 || self.best_count == 0 {
+            return Err(String::from(
+                "generations, runs_per_candidate and best_count have to be above 0",
+            ));
+        }
+        if !self.mutation_strength.is_finite() || self.mutation_strength < 0.0 {
+            return Err(String::from("mutation_strength can't be negative"));
+        }
+        if self.duration.is_zero() || self.time_step.is_zero() {
+            return Err(String::from("duration and time_step have to be above 0"));
+        }
+        self.detection
+            .validate()
+            .map_err(|error| format!("detection: {}", error))
+    }
+}
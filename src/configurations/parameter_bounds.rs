@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    generation_configuration::GenerationConfiguration,
+    species_generation_configuration::NUMERIC_PARAMETERS,
+};
+
+/// The values one parameter of a species is allowed to take.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParameterBounds {
+    pub species: String,
+    /// One of [`NUMERIC_PARAMETERS`].
+    pub parameter: String,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParameterBounds {
+    /// The name of the parameter in tables, like `Herbivore.walk_speed_s`.
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.species, self.parameter)
+    }
+
+    /// Checks that the parameter exists in `config` and that `config` stays valid
+    /// with the parameter at either bound.
+    pub fn validate(&self, config: &GenerationConfiguration) -> Result<(), String> {
+        if !config
+            .species
+            .iter()
+            .any(|species_config| species_config.species.name == self.species)
+        {
+            return Err(format!("unknown species {}", self.species));
+        }
+        if !NUMERIC_PARAMETERS.contains(&self.parameter.as_str()) {
+            return Err(format!("unknown parameter {}", self.parameter));
+        }
+        if !self.min.is_finite() || !self.max.is_finite() || self.min < 0.0 {
+            return Err(format!(
+                "range of {} has to be finite and not negative",
+                self.name()
+            ));
+        }
+        if self.min > self.max {
+            return Err(format!("range of {} ends before it starts", self.name()));
+        }
+        for value in [self.min, self.max] {
+            let mut bounded = config.clone();
+            self.apply(&mut bounded, value)
+                .and_then(|_| bounded.validate())
+                .map_err(|error| format!("range of {} is too wide: {}", self.name(), error))?;
+        }
+        Ok(())
+    }
+
+    /// The value of the parameter in `config`, None if it isn't there.
+    pub fn value_in(&self, config: &GenerationConfiguration) -> Option<f32> {
+        config
+            .species
+            .iter()
+            .find(|species_config| species_config.species.name == self.species)
+            .and_then(|species_config| species_config.parameter(&self.parameter))
+    }
+
+    /// Sets the parameter in `config` to `value`.
+    pub fn apply(&self, config: &mut GenerationConfiguration, value: f32) -> Result<(), String> {
+        config
+            .species
+            .iter_mut()
+            .find(|species_config| species_config.species.name == self.species)
+            .ok_or_else(|| format!("unknown species {}", self.species))?
+            .set_parameter(&self.parameter, value)
+    }
+}
@@ -0,0 +1,118 @@
+use std::{fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::organisms::species::SPECIES_NUMERIC_PARAMETERS;
+
+use super::generation_configuration::GenerationConfiguration;
+
+/// Events that happen to a simulation at set simulated times,
+/// on top of the initial population of the [`GenerationConfiguration`].
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    pub events: Vec<ScenarioEvent>,
+    /// Seeds the random numbers of the simulation in the window, so that the scenario
+    /// plays out the same way every time. Headless runs use their own seeds.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    /// Simulated time since the start of the simulation.
+    pub time: Duration,
+    pub action: ScenarioAction,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ScenarioAction {
+    /// Adds newborn organisms within `radius` of `center`.
+    Introduce {
+        species: String,
+        amount: u32,
+        center: [f32; 2],
+        radius: f32,
+    },
+    /// Removes a random `percent` of the living organisms of a species.
+    Cull { species: String, percent: f32 },
+    /// Changes a parameter of the species and its living organisms.
+    ChangeParameter {
+        species: String,
+        parameter: String,
+        value: f32,
+    },
+    /// No species photosynthesizes until the drought is over.
+    Drought { duration: Duration },
+}
+
+impl Scenario {
+    /// Reads the scenario in `path` and checks it against `config`.
+    pub fn load(path: &Path, config: &GenerationConfiguration) -> Result<Self, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|error| format!("reading {} failed: {}", path.display(), error))?;
+        let scenario: Self = serde_json::from_str(&json)
+            .map_err(|error| format!("parsing {} failed: {}", path.display(), error))?;
+        scenario
+            .validate(config)
+            .map_err(|error| format!("{} is invalid: {}", path.display(), error))?;
+        Ok(scenario)
+    }
+
+    /// Whether an event adds organisms of `species`.
+    pub fn introduces(&self, species: &str) -> bool {
+        self.events.iter().any(|event| {
+            matches!(&event.action, ScenarioAction::Introduce { species: introduced, .. } if introduced == species)
+        })
+    }
+
+    /// Checks that the events only refer to species and parameters that exist.
+    pub fn validate(&self, config: &GenerationConfiguration) -> Result<(), String> {
+        let species_exists = |name: &str| {
+            config
+                .species
+                .iter()
+                .any(|species_config| species_config.species.name == name)
+        };
+
+        for event in self.events.iter() {
+            let time = event.time.as_secs_f32();
+            match &event.action {
+                ScenarioAction::Introduce {
+                    species, radius, ..
+                } => {
+                    if !species_exists(species) {
+                        return Err(format!("unknown species {} at {}s", species, time));
+                    }
+                    if !radius.is_finite() || *radius < 0.0 {
+                        return Err(format!("radius can't be {} at {}s", radius, time));
+                    }
+                }
+                ScenarioAction::Cull { species, percent } => {
+                    if !species_exists(species) {
+                        return Err(format!("unknown species {} at {}s", species, time));
+                    }
+                    if !(0.0..=100.0).contains(percent) {
+                        return Err(format!("can't cull {}% at {}s", percent, time));
+                    }
+                }
+                ScenarioAction::ChangeParameter {
+                    species,
+                    parameter,
+                    value,
+                } => {
+                    if !species_exists(species) {
+                        return Err(format!("unknown species {} at {}s", species, time));
+                    }
+                    if !SPECIES_NUMERIC_PARAMETERS.contains(&parameter.as_str()) {
+                        return Err(format!("unknown parameter {} at {}s", parameter, time));
+                    }
+                    if !value.is_finite() || *value < 0.0 {
+                        return Err(format!("{} can't be {} at {}s", parameter, value, time));
+                    }
+                }
+                ScenarioAction::Drought { .. } => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,8 +2,51 @@ use serde::{Deserialize, Serialize};
 
 use crate::organisms::species::Species;
 
+/// Names of the numeric parameters that can be read and changed by name:
+/// `amount_per_meter` and [`crate::organisms::species::SPECIES_NUMERIC_PARAMETERS`].
+pub const NUMERIC_PARAMETERS: [&str; 13] = [
+    "amount_per_meter",
+    "max_energy",
+    "max_health",
+    "max_age",
+    "energy_cost_of_birth",
+    "health_cost_of_birth",
+    "walk_speed_s",
+    "photosynthesis_rate_s",
+    "eyesight_distance",
+    "birth_distance",
+    "birth_immunity",
+    "eating_distance",
+    "max_per_meter",
+];
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SpeciesGenerationConfiguration {
     pub species: Species,
     pub amount_per_meter: f32,
 }
+
+impl SpeciesGenerationConfiguration {
+    /// The value of one of [`NUMERIC_PARAMETERS`], None for unknown names.
+    pub fn parameter(&self, name: &str) -> Option<f32> {
+        match name {
+            "amount_per_meter" => Some(self.amount_per_meter),
+            _ => self.species.parameter(name),
+        }
+    }
+
+    /// Changes one of [`NUMERIC_PARAMETERS`].
+    /// None of them make sense negative, so negative values are refused.
+    pub fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), String> {
+        match name {
+            "amount_per_meter" if !value.is_finite() || value < 0.0 => {
+                Err(format!("{} can't be {}", name, value))
+            }
+            "amount_per_meter" => {
+                self.amount_per_meter = value;
+                Ok(())
+            }
+            _ => self.species.set_parameter(name, value),
+        }
+    }
+}
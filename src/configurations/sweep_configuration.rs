@@ -0,0 +1,111 @@
+use std::{fs, path::Path, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    detection_configuration::DetectionConfiguration,
+    generation_configuration::GenerationConfiguration, parameter_bounds::ParameterBounds,
+    scenario::Scenario,
+};
+
+/// Many headless runs of a base configuration with some of its parameters changed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SweepConfiguration {
+    /// The [`GenerationConfiguration`] the parameters are changed in.
+    pub base_config: PathBuf,
+    pub parameters: Vec<ParameterRange>,
+    pub sampling: Sampling,
+    /// How many differently seeded runs each combination of parameters gets.
+    /// Every combination uses the same seeds.
+    pub runs_per_combination: u32,
+    pub seed: u64,
+    /// How much time every run simulates.
+    pub duration: Duration,
+    pub time_step: Duration,
+    /// How often populations are counted for their mean and variance.
+    pub sample_interval: Duration,
+    /// Where the summary table is written, as csv.
+    pub output: PathBuf,
+    /// What is detected in the runs, and which of it ends them early.
+    #[serde(default)]
+    pub detection: DetectionConfiguration,
+    /// A [`Scenario`] played in every run, checked against the base configuration.
+    /// Its seed is ignored, the runs have their own.
+    #[serde(default)]
+    pub scenario: Option<PathBuf>,
+}
+
+/// The values one parameter of a species takes in a sweep.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParameterRange {
+    #[serde(flatten)]
+    pub bounds: ParameterBounds,
+    /// How many evenly spaced values from `min` to `max` a grid uses.
+    pub steps: u32,
+}
+
+impl ParameterRange {
+    /// The values a grid goes through.
+    pub fn grid_values(&self) -> Vec<f32> {
+        let ParameterBounds { min, max, .. } = self.bounds;
+        if self.steps <= 1 {
+            return vec![min];
+        }
+        (0..self.steps)
+            .map(|i| min + (max - min) * i as f32 / (self.steps - 1) as f32)
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Sampling {
+    /// Every combination of the values of every parameter.
+    Grid,
+    /// `samples` combinations with every value picked uniformly from its range.
+    Random { samples: u32 },
+}
+
+impl SweepConfiguration {
+    /// Reads and validates the sweep in `path`, returning it with its base configuration.
+    pub fn load(path: &Path) -> Result<(Self, GenerationConfiguration), String> {
+        let json = fs::read_to_string(path)
+            .map_err(|error| format!("reading {} failed: {}", path.display(), error))?;
+        let sweep: Self = serde_json::from_str(&json)
+            .map_err(|error| format!("parsing {} failed: {}", path.display(), error))?;
+        let base_config = GenerationConfiguration::load(&sweep.base_config)?;
+        sweep
+            .validate(&base_config)
+            .map_err(|error| format!("{} is invalid: {}", path.display(), error))?;
+        Ok((sweep, base_config))
+    }
+
+    /// The scenario every run plays, an empty one if there is none.
+    pub fn load_scenario(&self, base_config: &GenerationConfiguration) -> Result<Scenario, String> {
+        match &self.scenario {
+            Some(path) => Scenario::load(path, base_config),
+            None => Ok(Scenario::default()),
+        }
+    }
+
+    /// Checks that the parameters exist in `base_config` and the runs can be made.
+    pub fn validate(&self, base_config: &GenerationConfiguration) -> Result<(), String> {
+        for range in self.parameters.iter() {
+            range.bounds.validate(base_config)?;
+        }
+
+        if let Sampling::Random { samples: 0 } = self.sampling {
+            return Err(String::from("random sampling needs at least 1 sample"));
+        }
+        if self.runs_per_combination == 0 {
+            return Err(String::from("every combination needs at least 1 run"));
+        }
+        if self.duration.is_zero() || self.time_step.is_zero() || self.sample_interval.is_zero() {
+            return Err(String::from(
+                "duration, time_step and sample_interval have to be above 0",
+            ));
+        }
+        self.detection
+            .validate()
+            .map_err(|error| format!("detection: {}", error))
+    }
+}
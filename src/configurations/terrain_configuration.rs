@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use ggez::{graphics::Rect, mint::Point2};
+use serde::{Deserialize, Serialize};
+
+use crate::vector_helper;
+
+/// What the ground is like in the world.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainConfiguration {
+    pub source: TerrainSource,
+    pub biomes: BiomeTable,
+    /// Rocks and walls on top of the biomes. Nothing walks through or sees past them.
+    pub obstacles: Vec<Obstacle>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum TerrainSource {
+    /// Grassland everywhere.
+    #[default]
+    Flat,
+    /// An image stretched over the whole world, one cell per pixel.
+    /// From dark to bright the pixels are water, grassland, forest and desert.
+    Image { path: PathBuf },
+    /// Terrain generated from noise, the same for the same seed.
+    Procedural {
+        seed: u64,
+        /// Size of a cell in world units.
+        cell_size: f32,
+        /// About how big lakes and forests are, in world units.
+        feature_size: f32,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Biome {
+    Grassland,
+    Desert,
+    Water,
+    Forest,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 4] = [Biome::Grassland, Biome::Desert, Biome::Water, Biome::Forest];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::Grassland => "grassland",
+            Biome::Desert => "desert",
+            Biome::Water => "water",
+            Biome::Forest => "forest",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Obstacle {
+    Circle {
+        center: [f32; 2],
+        radius: f32,
+    },
+    /// `x` and `y` are the top left corner.
+    Rectangle {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    },
+}
+
+impl Obstacle {
+    pub fn contains(&self, point: Point2<f32>) -> bool {
+        match *self {
+            Obstacle::Circle { center, radius } => {
+                vector_helper::distance(center.into(), point) <= radius
+            }
+            Obstacle::Rectangle { x, y, w, h } => Rect::new(x, y, w, h).contains(point),
+        }
+    }
+
+    /// Whether the straight line from `from` to `to` touches the obstacle.
+    pub fn intersects_segment(&self, from: Point2<f32>, to: Point2<f32>) -> bool {
+        let direction = [to.x - from.x, to.y - from.y];
+        match *self {
+            Obstacle::Circle { center, radius } => {
+                let length_squared = vecmath::vec2_square_len(direction);
+                let to_center = [center[0] - from.x, center[1] - from.y];
+                let t = if length_squared == 0.0 {
+                    0.0
+                } else {
+                    (vecmath::vec2_dot(to_center, direction) / length_squared).clamp(0.0, 1.0)
+                };
+                let closest = Point2 {
+                    x: from.x + direction[0] * t,
+                    y: from.y + direction[1] * t,
+                };
+                vector_helper::distance(center.into(), closest) <= radius
+            }
+            Obstacle::Rectangle { x, y, w, h } => {
+                // clips the line to the rectangle one axis at a time
+                let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+                for (start, delta, low, high) in [
+                    (from.x, direction[0], x, x + w),
+                    (from.y, direction[1], y, y + h),
+                ] {
+                    if delta == 0.0 {
+                        if start < low || start > high {
+                            return false;
+                        }
+                        continue;
+                    }
+                    let t1 = (low - start) / delta;
+                    let t2 = (high - start) / delta;
+                    t_min = t_min.max(t1.min(t2));
+                    t_max = t_max.min(t1.max(t2));
+                    if t_min > t_max {
+                        return false;
+                    }
+                }
+                true
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let values: Vec<f32> = match *self {
+            Obstacle::Circle { center, radius } => vec![center[0], center[1], radius],
+            Obstacle::Rectangle { x, y, w, h } => vec![x, y, w, h],
+        };
+        if values.iter().any(|value| !value.is_finite()) {
+            return Err(String::from("obstacles need finite coordinates"));
+        }
+        let has_size = match *self {
+            Obstacle::Circle { radius, .. } => radius > 0.0,
+            Obstacle::Rectangle { w, h, .. } => w > 0.0 && h > 0.0,
+        };
+        if !has_size {
+            return Err(String::from("obstacles need a size above 0"));
+        }
+        Ok(())
+    }
+}
+
+/// How a biome affects the organisms in it.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct BiomeProperties {
+    /// Multiplies the photosynthesis rate.
+    pub photosynthesis: f32,
+    /// Multiplies the walk speed.
+    pub walk_speed: f32,
+    /// Organisms can't walk into impassable cells.
+    pub impassable: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BiomeTable {
+    pub grassland: BiomeProperties,
+    pub desert: BiomeProperties,
+    pub water: BiomeProperties,
+    pub forest: BiomeProperties,
+}
+
+impl Default for BiomeTable {
+    fn default() -> Self {
+        Self {
+            grassland: BiomeProperties {
+                photosynthesis: 1.0,
+                walk_speed: 1.0,
+                impassable: false,
+            },
+            desert: BiomeProperties {
+                photosynthesis: 0.2,
+                walk_speed: 0.8,
+                impassable: false,
+            },
+            water: BiomeProperties {
+                photosynthesis: 0.0,
+                walk_speed: 0.3,
+                impassable: true,
+            },
+            forest: BiomeProperties {
+                photosynthesis: 1.5,
+                walk_speed: 0.6,
+                impassable: false,
+            },
+        }
+    }
+}
+
+impl BiomeTable {
+    pub fn get(&self, biome: Biome) -> &BiomeProperties {
+        match biome {
+            Biome::Grassland => &self.grassland,
+            Biome::Desert => &self.desert,
+            Biome::Water => &self.water,
+            Biome::Forest => &self.forest,
+        }
+    }
+}
+
+impl TerrainConfiguration {
+    pub fn validate(&self) -> Result<(), String> {
+        for obstacle in self.obstacles.iter() {
+            obstacle.validate()?;
+        }
+        for biome in Biome::ALL {
+            let properties = self.biomes.get(biome);
+            for (name, value) in [
+                ("photosynthesis", properties.photosynthesis),
+                ("walk_speed", properties.walk_speed),
+            ] {
+                if !value.is_finite() || value < 0.0 {
+                    return Err(format!("{} of {} can't be {}", name, biome.name(), value));
+                }
+            }
+        }
+
+        match &self.source {
+            TerrainSource::Flat => Ok(()),
+            TerrainSource::Image { path } if !path.is_file() => {
+                Err(format!("terrain image {} doesn't exist", path.display()))
+            }
+            TerrainSource::Image { .. } => Ok(()),
+            TerrainSource::Procedural {
+                cell_size,
+                feature_size,
+                ..
+            } => {
+                if cell_size.is_nan()
+                    || *cell_size < 0.5
+                    || !feature_size.is_finite()
+                    || *feature_size <= 0.0
+                {
+                    return Err(String::from(
+                        "terrain needs a cell_size of at least 0.5 and a feature_size above 0",
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+}
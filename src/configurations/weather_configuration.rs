@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Random weather and disasters. None of them happen unless they get a `mean_interval`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherConfiguration {
+    pub rain: SpellConfiguration,
+    pub drought: SpellConfiguration,
+    pub fire: FireConfiguration,
+    pub flood: FloodConfiguration,
+}
+
+/// A period of weather that changes how much plants photosynthesize.
+/// Only one rain or drought happens at a time.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SpellConfiguration {
+    /// How long it takes on average until the next one starts, None for never.
+    pub mean_interval: Option<Duration>,
+    pub duration: Duration,
+    /// What photosynthesis is multiplied by while it lasts.
+    pub photosynthesis: f32,
+}
+
+/// Fires start at a random plant and spread through cells full of plants.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FireConfiguration {
+    pub mean_interval: Option<Duration>,
+    /// Width and height of the cells fire spreads through in world units.
+    pub cell_size: f32,
+    /// Plants per square meter a cell needs to catch fire.
+    pub min_plant_density: f32,
+    /// The chance per second that a burning cell sets each of its neighbours on fire.
+    pub spread_chance_s: f32,
+    /// How long a cell burns. It can't catch fire again during the same fire.
+    pub burn_duration: Duration,
+    /// The chance per second that an organism in a burning cell dies.
+    pub kill_chance_s: f32,
+}
+
+/// Floods cover the terrain below a water level for a while.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FloodConfiguration {
+    pub mean_interval: Option<Duration>,
+    pub duration: Duration,
+    /// Terrain with a lower elevation is flooded, from 0 to 1.
+    /// Flat terrain has an elevation of 0.5 everywhere.
+    pub water_level: f32,
+    /// The chance per second that an organism in the flood drowns.
+    pub drown_chance_s: f32,
+}
+
+impl Default for WeatherConfiguration {
+    fn default() -> Self {
+        Self {
+            rain: SpellConfiguration {
+                mean_interval: None,
+                duration: Duration::from_secs(60),
+                photosynthesis: 1.5,
+            },
+            drought: SpellConfiguration {
+                mean_interval: None,
+                duration: Duration::from_secs(90),
+                photosynthesis: 0.3,
+            },
+            fire: FireConfiguration {
+                mean_interval: None,
+                cell_size: 5.0,
+                min_plant_density: 0.05,
+                spread_chance_s: 0.5,
+                burn_duration: Duration::from_secs(5),
+                kill_chance_s: 0.8,
+            },
+            flood: FloodConfiguration {
+                mean_interval: None,
+                duration: Duration::from_secs(30),
+                water_level: 0.4,
+                drown_chance_s: 0.1,
+            },
+        }
+    }
+}
+
+impl WeatherConfiguration {
+    pub fn validate(&self) -> Result<(), String> {
+        let spells = [("rain", &self.rain), ("drought", &self.drought)];
+        for (name, spell) in spells {
+            validate_interval(name, spell.mean_interval, spell.duration)?;
+            if !spell.photosynthesis.is_finite() || spell.photosynthesis < 0.0 {
+                return Err(format!("{} photosynthesis can't be negative", name));
+            }
+        }
+
+        let fire = &self.fire;
+        validate_interval("fire", fire.mean_interval, fire.burn_duration)?;
+        if !(fire.cell_size.is_finite() && fire.cell_size > 0.0) {
+            return Err(String::from("fire cell_size has to be above 0"));
+        }
+        let values = [
+            ("min_plant_density", fire.min_plant_density),
+            ("spread_chance_s", fire.spread_chance_s),
+            ("kill_chance_s", fire.kill_chance_s),
+        ];
+        for (name, value) in values {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("fire {} can't be {}", name, value));
+            }
+        }
+
+        let flood = &self.flood;
+        validate_interval("flood", flood.mean_interval, flood.duration)?;
+        if !(0.0..=1.0).contains(&flood.water_level) {
+            return Err(String::from("flood water_level has to be between 0 and 1"));
+        }
+        if !flood.drown_chance_s.is_finite() || flood.drown_chance_s < 0.0 {
+            return Err(String::from("flood drown_chance_s can't be negative"));
+        }
+        Ok(())
+    }
+}
+
+fn validate_interval(
+    name: &str,
+    mean_interval: Option<Duration>,
+    duration: Duration,
+) -> Result<(), String> {
+    if mean_interval.is_some_and(|interval| interval.is_zero()) {
+        return Err(format!("{} mean_interval has to be above 0", name));
+    }
+    if duration.is_zero() {
+        return Err(format!("{} needs a duration above 0", name));
+    }
+    Ok(())
+}
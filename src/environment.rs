@@ -1,37 +1,77 @@
 use std::{
     collections::{hash_map::RandomState, HashMap},
+    path::PathBuf,
     time::Duration,
 };
 
 use ggez::{
     context::Has,
     graphics::{
-        Canvas, Color, DrawMode, DrawParam, FillOptions, GraphicsContext, InstanceArray, Mesh,
-        Rect, Text,
+        Canvas, Color, DrawMode, DrawParam, GraphicsContext, InstanceArray, Mesh, Rect, Text,
     },
-    input::keyboard::KeyboardContext,
+    input::{keyboard::KeyboardContext, mouse::MouseButton},
     mint::Point2,
     winit::event::VirtualKeyCode,
     Context,
 };
 
 use crate::{
-    configurations::generation_configuration::GenerationConfiguration, layout_info::LayoutInfo,
+    camera_follow::{CameraFollow, FollowOnDeath},
+    configurations::{generation_configuration::GenerationConfiguration, scenario::Scenario},
+    interaction_tool::{InteractionTool, Tool},
+    layout_info::LayoutInfo,
+    organism_renderer::OrganismRenderer,
+    organisms::species::Species,
+    overlays::{
+        debug_layers::{DebugLayer, DebugLayers},
+        heatmap::Heatmap,
+        minimap::Minimap,
+        organism_inspector::OrganismInspector,
+        population_graph::PopulationGraph,
+        species_legend::SpeciesLegend,
+        terrain_layer::TerrainLayer,
+        weather_layer::WeatherLayer,
+    },
+    simulation::BOUNDARY_DISTANCE_FROM_CENTER,
     simulation_thread::SimulationThread,
+    weather::SpellKind,
 };
 
 const CAMERA_SPEED: f32 = 400.0;
 const ZOOM_SPEED: f32 = 1.4;
+const WHEEL_ZOOM_FACTOR: f32 = 1.15;
+const MIN_ZOOM: f32 = 0.01;
+const MAX_ZOOM: f32 = 10000.0;
+const FOLLOW_SMOOTHNESS: f32 = 6.0;
+const CRASH_DUMP_DIRECTORY: &str = "crash_dumps";
+/// The background at midnight and at noon, when the clock is turned on.
+const NIGHT_BACKGROUND: [f32; 3] = [0.0, 0.01, 0.05];
+const DAY_BACKGROUND: [f32; 3] = [0.12, 0.12, 0.1];
+/// How far the mouse has to move while painting before the brush is used again.
+const BRUSH_SPACING_PX: f32 = 8.0;
+
+/// Organisms are positioned relative to the center of the environment.
+const ORGANISM_LAYOUT: LayoutInfo = LayoutInfo {
+    raw_rect_in_parent: Rect {
+        x: 0.,
+        y: 0.,
+        w: 1.,
+        h: 1.,
+    },
+    anchor: Point2 { x: 0.5, y: 0.5 },
+    origin: Point2 { x: 0.5, y: 0.5 },
+    scale: Point2 { x: 1.0, y: 1.0 },
+    relative_size: Point2 { x: false, y: false },
+};
 
 pub struct Environment {
     step: i64,
     time: Duration,
     offset: Point2<f32>,
     zoom: f32,
-    circle_mesh: Option<Mesh>,
     layout_info: LayoutInfo,
     key_dictionary: HashMap<VirtualKeyCode, [f32; 2], RandomState>,
-    organisms_mesh: InstanceArray,
+    organism_renderer: OrganismRenderer,
     vertical_horizontal_lines: Option<(Mesh, Mesh)>,
     lines_horizontal_mesh: InstanceArray,
     lines_vertical_mesh: InstanceArray,
@@ -40,17 +80,83 @@ pub struct Environment {
     zoom_velocity: f32,
     simulation_thread: SimulationThread,
     time_step: Duration,
+    paused: bool,
+    display_screen_rect: Rect,
+    inspector: OrganismInspector,
+    camera_follow: Option<CameraFollow>,
+    follow_on_death: FollowOnDeath,
+    dragging_camera: bool,
+    population_graph: PopulationGraph,
+    species_legend: SpeciesLegend,
+    debug_layers: DebugLayers,
+    heatmap: Heatmap,
+    terrain_layer: TerrainLayer,
+    weather_layer: WeatherLayer,
+    minimap: Minimap,
+    interaction_tool: InteractionTool,
+    mouse_position: Point2<f32>,
+    /// How many simulation events were printed already.
+    logged_events: usize,
 }
 
 impl Environment {
     pub fn simulate(&mut self, mut steps: u32) {
+        if self.paused {
+            self.probe();
+            return;
+        }
+
         self.time += self.time_step * steps;
         self.step += steps as i64;
         while steps > 0 {
             self.simulation_thread.advance(self.time);
             steps -= 1;
         }
+        self.probe();
+    }
+
+    fn probe(&mut self) {
         self.simulation_thread.probe();
+        self.population_graph
+            .record(self.simulation_thread.last_data());
+        self.species_legend
+            .record(self.simulation_thread.last_data());
+        self.log_events();
+    }
+
+    fn log_events(&mut self) {
+        let events = &self.simulation_thread.last_data().events;
+        if events.len() < self.logged_events {
+            // the simulation restarted
+            self.logged_events = 0;
+        }
+        for event in events[self.logged_events..].iter() {
+            println!("{}", event.description());
+        }
+        self.logged_events = events.len();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if self.paused {
+            self.simulation_thread.pause();
+        } else {
+            self.simulation_thread.resume();
+        }
+    }
+
+    pub fn single_step(&mut self, steps: u32) {
+        self.time += self.time_step * steps;
+        self.step += steps as i64;
+        self.simulation_thread.single_step(steps);
+    }
+
+    pub fn shutdown(&mut self) {
+        self.simulation_thread.shutdown();
     }
 
     pub fn change_time_step(&mut self, time_step: Duration) {
@@ -62,18 +168,22 @@ impl Environment {
         ctx: &Context,
         initial_time_step: Duration,
         generation_configuration: &GenerationConfiguration,
+        scenario: Scenario,
     ) -> Environment {
         let mut layout_info = LayoutInfo::new_centered();
         layout_info.relative_size = Point2 { x: true, y: true };
 
-        let simulation_thread =
-            SimulationThread::new(initial_time_step, generation_configuration.clone());
+        let simulation_thread = SimulationThread::new(
+            initial_time_step,
+            generation_configuration.clone(),
+            scenario,
+            Some(PathBuf::from(CRASH_DUMP_DIRECTORY)),
+        );
 
         Environment {
             step: 0,
             offset: Point2 { x: 0., y: 0. },
             zoom: 100.0,
-            circle_mesh: Option::None,
             time: Duration::ZERO,
             layout_info,
             key_dictionary: HashMap::from([
@@ -82,7 +192,7 @@ impl Environment {
                 (VirtualKeyCode::Up, [0f32, 1f32]),
                 (VirtualKeyCode::Down, [0f32, -1f32]),
             ]),
-            organisms_mesh: InstanceArray::new(&ctx.gfx, Option::None),
+            organism_renderer: OrganismRenderer::new(&ctx.gfx),
             vertical_horizontal_lines: Option::None,
             lines_horizontal_mesh: InstanceArray::new(&ctx.gfx, Option::None),
             lines_vertical_mesh: InstanceArray::new(&ctx.gfx, Option::None),
@@ -91,66 +201,87 @@ impl Environment {
             zoom_velocity: 0.0,
             simulation_thread,
             time_step: initial_time_step,
+            paused: false,
+            display_screen_rect: Rect::default(),
+            inspector: OrganismInspector::new(),
+            camera_follow: None,
+            follow_on_death: FollowOnDeath::Corpse,
+            dragging_camera: false,
+            population_graph: PopulationGraph::new(),
+            species_legend: SpeciesLegend::new(),
+            debug_layers: DebugLayers::new(),
+            heatmap: Heatmap::new(),
+            terrain_layer: TerrainLayer::new(),
+            weather_layer: WeatherLayer::new(),
+            minimap: Minimap::new(),
+            interaction_tool: InteractionTool::new(),
+            mouse_position: Point2 { x: 0.0, y: 0.0 },
+            logged_events: 0,
         }
     }
 
-    pub fn draw(
-        &mut self,
-        canvas: &mut Canvas,
-        gfx: &impl Has<GraphicsContext>,
-    ) {
+    pub fn draw(&mut self, canvas: &mut Canvas, gfx: &impl Has<GraphicsContext>) {
         let display_screen_rect = canvas.screen_coordinates().unwrap();
+        self.display_screen_rect = display_screen_rect;
 
-        let zoom_container = LayoutInfo {
-            raw_rect_in_parent: Rect {
-                x: 0.,
-                y: 0.,
-                w: 1.,
-                h: 1.,
-            },
-            anchor: Point2 { x: 0.5, y: 0.5 },
-            origin: Point2 { x: 0.5, y: 0.5 },
-            scale: Point2 { x: 1.0, y: 1.0 },
-            relative_size: Point2 { x: true, y: true },
-        };
+        let environment_screen_rect = self.get_environment_screen_rect(&display_screen_rect);
 
-        let zoom_container_screen_rect = zoom_container.get_screen_rect(&display_screen_rect, 1.0);
+        self.terrain_layer
+            .draw(canvas, gfx, self.simulation_thread.last_data(), |point| {
+                ORGANISM_LAYOUT.get_screen_point(point, &environment_screen_rect, self.zoom)
+            });
 
-        let environment_screen_rect = self
-            .layout_info
-            .get_screen_rect(&zoom_container_screen_rect, self.zoom);
+        self.weather_layer
+            .draw(canvas, gfx, self.simulation_thread.last_data(), |point| {
+                ORGANISM_LAYOUT.get_screen_point(point, &environment_screen_rect, self.zoom)
+            });
 
         self.draw_lines(canvas, &display_screen_rect, &environment_screen_rect, gfx);
 
-        let circle_mesh = self
-            .circle_mesh
-            .get_or_insert(Self::get_new_circle_mesh(gfx));
-        // for organism in self.organisms.iter() {
-        //     organism.draw(
-        //         &environment_screen_rect,
-        //         self.zoom,
-        //         canvas,
-        //         gfx,
-        //         circle_mesh,
-        //         application_context,
-        //     );
-        // }
+        self.heatmap
+            .draw(canvas, gfx, self.simulation_thread.last_data(), |point| {
+                ORGANISM_LAYOUT.get_screen_point(point, &environment_screen_rect, self.zoom)
+            });
+
         let visibility_rect = canvas.screen_coordinates().unwrap();
 
-        self.organisms_mesh.set(
+        self.organism_renderer.draw(
+            canvas,
             self.simulation_thread
-                .last_data
+                .last_data()
                 .organism_infos
                 .iter()
-                .filter_map(|o| {
-                    o.get_draw_param(&environment_screen_rect, self.zoom, &visibility_rect)
+                .filter(|o| {
+                    self.species_legend
+                        .is_visible(&o.shared_state().species.name)
                 }),
+            &environment_screen_rect,
+            self.zoom,
+            &visibility_rect,
         );
-        // canvas.draw(&self.organisms_mesh, DrawParam::default());
-        canvas.draw_instanced_mesh(
-            circle_mesh.to_owned(),
-            &self.organisms_mesh,
-            DrawParam::default(),
+
+        self.debug_layers.draw(
+            canvas,
+            gfx,
+            self.simulation_thread.last_data(),
+            self.zoom,
+            |point| ORGANISM_LAYOUT.get_screen_point(point, &environment_screen_rect, self.zoom),
+            |info| {
+                self.species_legend
+                    .is_visible(&info.shared_state().species.name)
+            },
+        );
+
+        self.draw_brush(canvas, gfx);
+        self.draw_inspector(canvas, gfx, &display_screen_rect);
+        self.population_graph
+            .draw(canvas, gfx, &display_screen_rect);
+        self.draw_minimap(canvas, gfx, &display_screen_rect);
+        self.species_legend.draw(
+            canvas,
+            gfx,
+            &display_screen_rect,
+            self.simulation_thread.last_data(),
         );
 
         canvas.draw(
@@ -160,52 +291,252 @@ impl Environment {
                 {:.2} / {:.2} / {:.2}\n\n\
                 organisms:{}\n\
                 drawn:{}\n\n\
-                {}\nnth organism: {}",
-                self.simulation_thread.last_data.step,
+                nth organism: {}\n{}\n{}{}{}{}{}{}{}{}{}",
+                self.simulation_thread.last_data().step,
                 self.step,
-                self.step - self.simulation_thread.last_data.step as i64,
-                self.simulation_thread.last_data.time.as_secs_f32(),
+                self.step - self.simulation_thread.last_data().step as i64,
+                self.simulation_thread.last_data().time.as_secs_f32(),
                 self.time.as_secs_f32(),
-                self.time.as_secs_f32() - self.simulation_thread.last_data.time.as_secs_f32(),
-                self.simulation_thread.last_data.organism_infos.len(),
-                self.organisms_mesh.instances().len(),
-                Self::species_count_string(&self.simulation_thread.last_data.organism_counter),
+                self.time.as_secs_f32() - self.simulation_thread.last_data().time.as_secs_f32(),
+                self.simulation_thread.last_data().organism_infos.len(),
+                self.organism_renderer.drawn_count(),
                 self.simulate_every_n_organism,
+                self.interaction_tool.description(),
+                self.camera_follow_string(),
+                match self.heatmap.description() {
+                    Some(heatmap) => format!("\nheatmap: {}", heatmap),
+                    None => String::new(),
+                },
+                match self.debug_layers.description() {
+                    Some(layers) => format!("\ndebug: {}", layers),
+                    None => String::new(),
+                },
+                match self.simulation_thread.last_data().events.last() {
+                    Some(event) => format!("\nlast event: {}", event.description()),
+                    None => String::new(),
+                },
+                match self.simulation_thread.last_data().clock {
+                    Some(clock) => format!("\n{}", clock.description()),
+                    None => String::new(),
+                },
+                self.weather_description(),
+                if self.simulation_thread.last_data().nutrients.is_enabled() {
+                    format!(
+                        "\nnutrients: {:.0}",
+                        self.simulation_thread.last_data().nutrients.total()
+                    )
+                } else {
+                    String::new()
+                },
+                match self.simulation_thread.last_data().pending_scenario_events {
+                    0 => String::new(),
+                    pending => format!("\nscenario events left: {}", pending),
+                },
+                if self.paused { "\n\nPAUSED" } else { "" },
             )),
             DrawParam::default(),
         );
+
+        self.draw_simulation_failure(canvas, &display_screen_rect);
     }
 
-    fn species_count_string(organism_counter: &HashMap<String, u32>) -> String {
-        let mut s = String::with_capacity(100);
-        for (species_name, species_count) in organism_counter {
-            s += &format!("{}: {}\n", species_name, species_count);
+    fn get_environment_screen_rect(&self, display_screen_rect: &Rect) -> Rect {
+        let zoom_container = LayoutInfo {
+            raw_rect_in_parent: Rect {
+                x: 0.,
+                y: 0.,
+                w: 1.,
+                h: 1.,
+            },
+            anchor: Point2 { x: 0.5, y: 0.5 },
+            origin: Point2 { x: 0.5, y: 0.5 },
+            scale: Point2 { x: 1.0, y: 1.0 },
+            relative_size: Point2 { x: true, y: true },
+        };
+
+        let zoom_container_screen_rect = zoom_container.get_screen_rect(display_screen_rect, 1.0);
+
+        self.layout_info
+            .get_screen_rect(&zoom_container_screen_rect, self.zoom)
+    }
+
+    /// The weather going on right now, for the HUD.
+    fn weather_description(&self) -> String {
+        let weather = &self.simulation_thread.last_data().weather;
+        let mut parts = Vec::new();
+        if let Some((kind, time_left)) = weather.spell {
+            let name = match kind {
+                SpellKind::Rain => "rain",
+                SpellKind::Drought => "drought",
+            };
+            parts.push(format!("{} ({:.0}s left)", name, time_left.as_secs_f32()));
+        }
+        if !weather.burning.is_empty() {
+            parts.push(String::from("fire"));
+        }
+        if weather.flood_level.is_some() {
+            parts.push(String::from("flood"));
         }
-        s
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!("\nweather: {}", parts.join(", "))
+        }
+    }
+
+    /// Black without the clock, otherwise brighter the more light there is.
+    pub fn background_color(&self) -> Color {
+        let Some(clock) = self.simulation_thread.last_data().clock else {
+            return Color::BLACK;
+        };
+        let [r, g, b] = [0, 1, 2].map(|channel| {
+            NIGHT_BACKGROUND[channel]
+                + (DAY_BACKGROUND[channel] - NIGHT_BACKGROUND[channel]) * clock.light
+        });
+        Color::new(r, g, b, 1.0)
+    }
+
+    /// Converts a point on the screen into world coordinates.
+    pub fn screen_to_world(&self, screen_point: Point2<f32>) -> Point2<f32> {
+        let environment_screen_rect = self.get_environment_screen_rect(&self.display_screen_rect);
+        ORGANISM_LAYOUT.get_point_in_parent(screen_point, &environment_screen_rect, self.zoom)
+    }
+
+    /// Converts a point in world coordinates into a point on the screen.
+    pub fn world_to_screen(&self, world_point: Point2<f32>) -> Point2<f32> {
+        let environment_screen_rect = self.get_environment_screen_rect(&self.display_screen_rect);
+        ORGANISM_LAYOUT.get_screen_point(world_point, &environment_screen_rect, self.zoom)
     }
 
-    fn get_new_circle_mesh(gfx: &impl Has<GraphicsContext>) -> Mesh {
-        let size = 10.0;
-        Mesh::new_circle(
+    fn draw_inspector(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        display_screen_rect: &Rect,
+    ) {
+        let Some(selected_id) = self.inspector.selected() else {
+            return;
+        };
+
+        // the answer arrives with one of the next probes
+        self.simulation_thread.request_organism_details(selected_id);
+
+        let info = self
+            .simulation_thread
+            .last_data()
+            .organism_infos
+            .iter()
+            .find(|info| info.id() == selected_id);
+
+        if let Some(info) = info {
+            let screen_position = self.world_to_screen(info.shared_state().position);
+            self.inspector
+                .draw_highlight(canvas, gfx, info, screen_position, self.zoom);
+        }
+
+        self.inspector.draw_panel(
+            canvas,
             gfx,
-            DrawMode::Fill(FillOptions::DEFAULT),
-            Point2 { x: 0.0, y: 0.0 },
-            size * 0.5,
-            0.07,
-            Color::WHITE,
-        )
-        // Mesh::new_rectangle(
-        //     gfx,
-        //     DrawMode::Fill(FillOptions::DEFAULT),
-        //     Rect {
-        //         x: -size * 0.5,
-        //         y: -size * 0.5,
-        //         w: size,
-        //         h: size,
-        //     },
-        //     Color::WHITE,
-        // )
-        .unwrap()
+            display_screen_rect,
+            info,
+            self.simulation_thread.organism_details(selected_id),
+        );
+    }
+
+    /// Shows where the spawn and remove tools reach.
+    fn draw_brush(&self, canvas: &mut Canvas, gfx: &impl Has<GraphicsContext>) {
+        let color = match self.interaction_tool.tool() {
+            Tool::Select => return,
+            Tool::Spawn => Color::new(0.3, 1.0, 0.3, 0.8),
+            Tool::Remove => Color::new(1.0, 0.3, 0.3, 0.8),
+        };
+        let radius = (self.interaction_tool.effective_radius(self.zoom) * self.zoom).max(3.0);
+        let mesh = Mesh::new_circle(
+            gfx,
+            DrawMode::stroke(1.5),
+            self.mouse_position,
+            radius,
+            (radius * 0.005).max(0.1),
+            color,
+        );
+        if let Ok(mesh) = mesh {
+            canvas.draw(&mesh, DrawParam::default());
+        }
+    }
+
+    fn draw_minimap(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        display_screen_rect: &Rect,
+    ) {
+        // stacked on top of the population graph, which shares the corner
+        let corner = Point2 {
+            x: display_screen_rect.right(),
+            y: self
+                .population_graph
+                .screen_rect()
+                .map_or(display_screen_rect.bottom(), |graph_rect| graph_rect.top()),
+        };
+
+        let top_left = self.screen_to_world(display_screen_rect.point());
+        let bottom_right = self.screen_to_world(Point2 {
+            x: display_screen_rect.right(),
+            y: display_screen_rect.bottom(),
+        });
+        let viewport = Rect::new(
+            top_left.x,
+            top_left.y,
+            bottom_right.x - top_left.x,
+            bottom_right.y - top_left.y,
+        );
+
+        self.minimap.draw(
+            canvas,
+            gfx,
+            self.simulation_thread.last_data(),
+            corner,
+            viewport,
+            &self.species_legend,
+        );
+    }
+
+    fn draw_simulation_failure(&self, canvas: &mut Canvas, display_screen_rect: &Rect) {
+        let report = if let Some(panic) = self.simulation_thread.panic() {
+            let dump = match &panic.snapshot_dump {
+                Some(path) => format!("snapshot dumped to {}", path.display()),
+                None => String::from("no snapshot dumped"),
+            };
+            format!(
+                "simulation panicked at step {} ({:.2}s):\n{}\n{}\n\npress R to restart",
+                panic.step,
+                panic.time.as_secs_f32(),
+                panic.message,
+                dump,
+            )
+        } else if !self.simulation_thread.is_running() {
+            String::from("simulation thread stopped")
+        } else {
+            return;
+        };
+
+        canvas.draw(
+            &Text::new(report),
+            DrawParam::default()
+                .dest([display_screen_rect.w * 0.3, display_screen_rect.h * 0.4])
+                .color(Color::RED),
+        );
+    }
+
+    fn camera_follow_string(&self) -> String {
+        match &self.camera_follow {
+            Some(camera_follow) => format!(
+                "following #{} (on death: {})",
+                camera_follow.organism_id(),
+                self.follow_on_death.name()
+            ),
+            None => format!("on death: {}", self.follow_on_death.name()),
+        }
     }
 
     fn calculate_lines_distance(zoom: f32) -> f32 {
@@ -307,15 +638,19 @@ impl Environment {
         if self.zoom.is_nan() {
             self.zoom = 1.0;
         } else {
-            self.zoom = self.zoom.clamp(0.01, 10000.0);
+            self.zoom = self.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
         }
 
         let camera_moving_direction = self.direction_from_keyboard_state(&ctx.keyboard);
 
         if camera_moving_direction == [0f32, 0f32] {
+            self.follow_organism(ctx.time.delta().as_secs_f32());
             return;
         }
 
+        // steering by hand takes the camera back
+        self.camera_follow = None;
+
         let offset: [f32; 2] = vecmath::vec2_scale(
             vecmath::vec2_normalized(camera_moving_direction),
             CAMERA_SPEED * ctx.time.delta().as_secs_f32() / self.zoom,
@@ -324,6 +659,58 @@ impl Environment {
         self.layout_info.raw_rect_in_parent.translate(offset);
     }
 
+    /// Keeps the followed organism in the middle of the screen.
+    fn follow_organism(&mut self, delta_s: f32) {
+        let Some(camera_follow) = self.camera_follow.as_mut() else {
+            return;
+        };
+
+        let previous_id = camera_follow.organism_id();
+        let target = camera_follow.find_target(
+            &self.simulation_thread.last_data().organism_infos,
+            self.follow_on_death,
+        );
+        let Some(target) = target else {
+            self.camera_follow = None;
+            return;
+        };
+        let target_position = target.shared_state().position;
+
+        // keep inspecting whatever the camera switched to
+        if self.inspector.selected() == Some(previous_id) {
+            self.inspector.select(Some(target.id()));
+        }
+
+        // the environment is offset in the opposite direction of the camera
+        let raw_rect = &mut self.layout_info.raw_rect_in_parent;
+        let camera_position = Point2 {
+            x: -raw_rect.x,
+            y: -raw_rect.y,
+        };
+        let new_camera_position = CameraFollow::smooth_towards(
+            camera_position,
+            target_position,
+            FOLLOW_SMOOTHNESS,
+            delta_s,
+        );
+        raw_rect.x = -new_camera_position.x;
+        raw_rect.y = -new_camera_position.y;
+    }
+
+    /// Moves the camera so that `world_point` is in the middle of the screen.
+    pub fn center_camera_on(&mut self, world_point: Point2<f32>) {
+        self.camera_follow = None;
+        self.layout_info.raw_rect_in_parent.x = -world_point.x;
+        self.layout_info.raw_rect_in_parent.y = -world_point.y;
+    }
+
+    pub fn toggle_follow_selected(&mut self) {
+        self.camera_follow = match (&self.camera_follow, self.inspector.selected()) {
+            (None, Some(selected_id)) => Some(CameraFollow::new(selected_id)),
+            _ => None,
+        };
+    }
+
     fn direction_from_keyboard_state(&self, ctx: &KeyboardContext) -> [f32; 2] {
         let mut direction = [0f32, 0f32];
         for (key, vector) in &self.key_dictionary {
@@ -348,17 +735,41 @@ impl Environment {
                 VirtualKeyCode::Minus if !_repeated => {
                     self.zoom_velocity -= 1.0;
                 }
-                VirtualKeyCode::PageDown => {
-                    if self.simulate_every_n_organism > 1 {
-                        self.simulate_every_n_organism -= 1;
-                    }
+                VirtualKeyCode::PageDown if self.simulate_every_n_organism > 1 => {
+                    self.simulate_every_n_organism -= 1;
                 }
-                VirtualKeyCode::PageUp => {
-                    if self.simulate_every_n_organism < 32 {
-                        self.simulate_every_n_organism += 1;
-                    }
+                VirtualKeyCode::PageUp if self.simulate_every_n_organism < 32 => {
+                    self.simulate_every_n_organism += 1;
                 }
                 VirtualKeyCode::X => self.cull_organisms_outside_view = true,
+                VirtualKeyCode::Escape => self.inspector.select(None),
+                VirtualKeyCode::F => self.toggle_follow_selected(),
+                VirtualKeyCode::L => self.follow_on_death = self.follow_on_death.next(),
+                VirtualKeyCode::Home => self.fit_world_to_window(),
+                VirtualKeyCode::G => self.population_graph.toggle_visible(),
+                VirtualKeyCode::LBracket => self.population_graph.shorten_history_window(),
+                VirtualKeyCode::RBracket => self.population_graph.lengthen_history_window(),
+                VirtualKeyCode::Tab => {
+                    self.interaction_tool.next_tool();
+                    if self.interaction_tool.species_name().is_none() {
+                        self.interaction_tool
+                            .next_species(self.simulation_thread.last_data());
+                    }
+                }
+                VirtualKeyCode::C => self
+                    .interaction_tool
+                    .next_species(self.simulation_thread.last_data()),
+                VirtualKeyCode::M => self.minimap.toggle_visible(),
+                VirtualKeyCode::T => self.terrain_layer.toggle_visible(),
+                VirtualKeyCode::H => self.heatmap.next_mode(),
+                VirtualKeyCode::J => self.heatmap.next_ramp(),
+                VirtualKeyCode::K => self
+                    .heatmap
+                    .next_species(self.simulation_thread.last_data()),
+                VirtualKeyCode::F1 => self.debug_layers.toggle(DebugLayer::Eyesight),
+                VirtualKeyCode::F2 => self.debug_layers.toggle(DebugLayer::Hunting),
+                VirtualKeyCode::F3 => self.debug_layers.toggle(DebugLayer::EatingDistance),
+                VirtualKeyCode::F4 => self.debug_layers.toggle(DebugLayer::Chunks),
                 _ => {}
             }
         } else if input.scancode == 12 && !_repeated {
@@ -367,6 +778,123 @@ impl Environment {
         }
     }
 
+    pub fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        if button == MouseButton::Left && self.population_graph.mouse_button_down_event(x, y) {
+            return;
+        }
+        if self.species_legend.mouse_button_down_event(button, x, y) {
+            return;
+        }
+        if let Some(world_point) = self.minimap.world_point_at(x, y) {
+            if button == MouseButton::Left {
+                self.center_camera_on(world_point);
+            }
+            return;
+        }
+
+        match button {
+            MouseButton::Left if self.interaction_tool.tool() == Tool::Select => {
+                let world_point = self.screen_to_world(Point2 { x, y });
+                self.inspector.select_nearest(
+                    &self.simulation_thread.last_data().organism_infos,
+                    world_point,
+                    self.zoom,
+                );
+            }
+            MouseButton::Left => self.use_tool(self.screen_to_world(Point2 { x, y })),
+            MouseButton::Right | MouseButton::Middle => self.dragging_camera = true,
+            _ => {}
+        }
+    }
+
+    pub fn mouse_button_up_event(&mut self, button: MouseButton) {
+        if button == MouseButton::Left {
+            self.population_graph.mouse_button_up_event();
+            self.interaction_tool.stop_painting();
+        }
+        if let MouseButton::Right | MouseButton::Middle = button {
+            self.dragging_camera = false;
+        }
+    }
+
+    pub fn mouse_motion_event(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.mouse_position = Point2 { x, y };
+        if self.population_graph.mouse_motion_event(dx, dy) {
+            return;
+        }
+        if self.interaction_tool.is_painting() {
+            let world_point = self.screen_to_world(self.mouse_position);
+            if self
+                .interaction_tool
+                .should_stamp(world_point, BRUSH_SPACING_PX / self.zoom)
+            {
+                self.use_tool(world_point);
+            }
+        }
+        if !self.dragging_camera {
+            return;
+        }
+        // dragging takes the camera back, like steering with the keyboard
+        self.camera_follow = None;
+        self.layout_info
+            .raw_rect_in_parent
+            .translate([dx / self.zoom, dy / self.zoom]);
+    }
+
+    /// Spawns or removes organisms around `world_point`, depending on the tool.
+    fn use_tool(&mut self, world_point: Point2<f32>) {
+        let radius = self.interaction_tool.effective_radius(self.zoom);
+        match self.interaction_tool.tool() {
+            Tool::Select => return,
+            Tool::Spawn => {
+                let Some(species_name) = self.interaction_tool.species_name() else {
+                    return;
+                };
+                self.simulation_thread.spawn_organisms(
+                    species_name.to_string(),
+                    world_point,
+                    radius,
+                    self.interaction_tool.spawn_amount(),
+                );
+            }
+            Tool::Remove => self.simulation_thread.remove_organisms(world_point, radius),
+        }
+        self.interaction_tool.stamped(world_point);
+    }
+
+    pub fn change_brush_radius(&mut self, amount: f32) {
+        self.interaction_tool.change_brush_radius(amount);
+    }
+
+    /// Zooms in or out, keeping the world point under the cursor in place.
+    pub fn mouse_wheel_event(&mut self, cursor: Point2<f32>, amount: f32) {
+        let new_zoom = (self.zoom * WHEEL_ZOOM_FACTOR.powf(amount)).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.zoom_around(cursor, new_zoom);
+    }
+
+    fn zoom_around(&mut self, screen_point: Point2<f32>, new_zoom: f32) {
+        let world_point_before = self.screen_to_world(screen_point);
+        self.zoom = new_zoom;
+        let world_point_after = self.screen_to_world(screen_point);
+
+        self.layout_info.raw_rect_in_parent.translate([
+            world_point_after.x - world_point_before.x,
+            world_point_after.y - world_point_before.y,
+        ]);
+    }
+
+    /// Centers the camera on the world and zooms so that all of it is visible.
+    pub fn fit_world_to_window(&mut self) {
+        self.camera_follow = None;
+        let world_size = 2.0 * BOUNDARY_DISTANCE_FROM_CENTER;
+        let screen_size = self.display_screen_rect.w.min(self.display_screen_rect.h);
+        if screen_size > 0.0 {
+            self.zoom = (screen_size / world_size).clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+        self.layout_info.raw_rect_in_parent.x = 0.0;
+        self.layout_info.raw_rect_in_parent.y = 0.0;
+    }
+
     pub fn key_up_event(&mut self, _ctx: &mut Context, input: ggez::input::keyboard::KeyInput) {
         if let Some(keycode) = input.keycode {
             match keycode {
@@ -384,7 +912,17 @@ impl Environment {
         }
     }
 
+    pub fn update_species(&mut self, species: Vec<Species>) {
+        self.simulation_thread.update_species(species);
+    }
+
     pub fn restart(&mut self, species_gen_config: GenerationConfiguration) {
+        self.camera_follow = None;
+        self.inspector.select(None);
+        self.population_graph.clear();
+        if self.paused {
+            self.toggle_pause();
+        }
         self.time = Duration::ZERO;
         self.step = 0;
         self.simulation_thread.restart(species_gen_config);
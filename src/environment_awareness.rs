@@ -1,22 +1,66 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use ggez::{graphics::Rect, mint::Point2};
 
-use crate::organisms::{organism::Organism, states::organism_state::ForeignerInfo};
+use crate::{
+    clock::ClockReading,
+    navigation::NavigationGrid,
+    organisms::{organism::Organism, states::organism_state::ForeignerInfo},
+    terrain::Terrain,
+};
 
 pub struct EnvironmentAwareness {
     chunk_size: f32,
     chunks: HashMap<Point2<i32>, Vec<ForeignerInfo>>,
+    /// Shared with the snapshots, which draw it.
+    terrain: Arc<Terrain>,
+    navigation: NavigationGrid,
+    /// The time of day and year during the current step.
+    clock: ClockReading,
+    /// What rain or drought multiply photosynthesis by during the current step.
+    weather_photosynthesis: f32,
 }
 
 impl EnvironmentAwareness {
-    pub fn new(chunk_size: f32) -> Self {
+    pub fn new(chunk_size: f32, terrain: Arc<Terrain>) -> Self {
         Self {
             chunk_size,
             chunks: HashMap::new(),
+            navigation: NavigationGrid::new(&terrain),
+            terrain,
+            clock: ClockReading::default(),
+            weather_photosynthesis: 1.0,
         }
     }
 
+    pub fn terrain(&self) -> &Terrain {
+        &self.terrain
+    }
+
+    pub fn terrain_arc(&self) -> &Arc<Terrain> {
+        &self.terrain
+    }
+
+    pub fn navigation(&self) -> &NavigationGrid {
+        &self.navigation
+    }
+
+    pub fn clock(&self) -> &ClockReading {
+        &self.clock
+    }
+
+    pub fn set_clock(&mut self, clock: ClockReading) {
+        self.clock = clock;
+    }
+
+    pub fn weather_photosynthesis(&self) -> f32 {
+        self.weather_photosynthesis
+    }
+
+    pub fn set_weather_photosynthesis(&mut self, photosynthesis: f32) {
+        self.weather_photosynthesis = photosynthesis;
+    }
+
     pub fn refill(&mut self, organisms: &Vec<Organism>) {
         for chunk in self.chunks.values_mut() {
             chunk.clear();
@@ -28,6 +72,10 @@ impl EnvironmentAwareness {
         }
     }
 
+    pub fn chunk_size(&self) -> f32 {
+        self.chunk_size
+    }
+
     pub fn get_chunks(&self) -> &HashMap<Point2<i32>, Vec<ForeignerInfo>> {
         &self.chunks
     }
@@ -84,12 +132,12 @@ impl EnvironmentAwareness {
 
     pub fn get_chunk_on_point(&mut self, point: Point2<f32>) -> &Vec<ForeignerInfo> {
         let index = self.get_chunk_index_on_point(point);
-        self.chunks.entry(index).or_insert_with(Vec::new)
+        self.chunks.entry(index).or_default()
     }
 
     pub fn get_chunk_on_point_mut(&mut self, point: Point2<f32>) -> &mut Vec<ForeignerInfo> {
         let index = self.get_chunk_index_on_point(point);
-        self.chunks.entry(index).or_insert_with(Vec::new)
+        self.chunks.entry(index).or_default()
     }
 
     pub fn get_radius_around(
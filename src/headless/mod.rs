@@ -0,0 +1,233 @@
+pub mod optimizer;
+pub mod sweep;
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    configurations::{
+        detection_configuration::DetectionConfiguration,
+        generation_configuration::GenerationConfiguration, scenario::Scenario,
+    },
+    random,
+    simulation::Simulation,
+    simulation_event::{SimulationEvent, SimulationEventKind},
+};
+
+/// How a simulation without a window is run.
+#[derive(Clone)]
+pub struct RunSettings {
+    pub seed: u64,
+    pub duration: Duration,
+    pub time_step: Duration,
+    /// How often populations are counted.
+    pub sample_interval: Duration,
+    /// Also says which events end the run early.
+    pub detection: DetectionConfiguration,
+    /// What happens during every run, at the same simulated times.
+    pub scenario: Scenario,
+}
+
+/// What happened to the configured species during one run.
+pub struct RunSummary {
+    pub species: Vec<SpeciesSummary>,
+    pub events: Vec<SimulationEvent>,
+    /// The event that ended the run before its duration, if one did.
+    pub stop_reason: Option<SimulationEvent>,
+}
+
+pub struct SpeciesSummary {
+    pub name: String,
+    /// When the last organism of the species first died, None if it survived.
+    pub extinction_time: Option<Duration>,
+    /// The population, counted every sample interval starting at 0.
+    pub populations: Vec<u32>,
+}
+
+impl RunSummary {
+    /// Whether every configured species is still alive at the end.
+    pub fn coexistence(&self) -> bool {
+        self.species
+            .iter()
+            .all(|species| species.extinction_time.is_none())
+    }
+}
+
+/// Runs a simulation on the current thread as fast as possible.
+/// The same settings always give the same result.
+pub fn run(config: &GenerationConfiguration, settings: &RunSettings) -> RunSummary {
+    random::seed(settings.seed);
+    let mut simulation = Simulation::new(config);
+    simulation.set_scenario(&settings.scenario);
+    simulation.set_detection_configuration(settings.detection.clone());
+
+    // the population monitor only reports species that were alive, but a species that never
+    // was would otherwise count as surviving the whole run
+    let initial_extinctions: Vec<SimulationEvent> = config
+        .species
+        .iter()
+        .map(|species_config| &species_config.species.name)
+        .filter(|name| {
+            simulation
+                .organism_counter()
+                .get(*name)
+                .copied()
+                .unwrap_or(0)
+                == 0
+                && !settings.scenario.introduces(name)
+        })
+        .map(|name| SimulationEvent {
+            time: Duration::ZERO,
+            kind: SimulationEventKind::Extinction {
+                species: name.clone(),
+            },
+        })
+        .collect();
+
+    let mut summary = RunSummary {
+        species: config
+            .species
+            .iter()
+            .map(|species_config| SpeciesSummary {
+                name: species_config.species.name.clone(),
+                extinction_time: None,
+                populations: Vec::new(),
+            })
+            .collect(),
+        stop_reason: initial_extinctions
+            .iter()
+            .find(|event| settings.detection.stops_on(&event.kind))
+            .cloned(),
+        events: initial_extinctions,
+    };
+
+    let mut next_sample = Duration::ZERO;
+    loop {
+        let time = simulation.time();
+        let counter = simulation.organism_counter();
+        if time >= next_sample {
+            for species in summary.species.iter_mut() {
+                let population = counter.get(&species.name).copied().unwrap_or(0);
+                species.populations.push(population);
+            }
+            next_sample += settings.sample_interval;
+        }
+
+        if time >= settings.duration || summary.stop_reason.is_some() {
+            break;
+        }
+        let seen_events = simulation.events().len();
+        simulation.run(settings.time_step.min(settings.duration - time));
+        summary.stop_reason = simulation.events()[seen_events..]
+            .iter()
+            .find(|event| settings.detection.stops_on(&event.kind))
+            .cloned();
+    }
+    summary.events.extend_from_slice(simulation.events());
+    // the population monitor of the simulation decides what counts as an extinction
+    for event in summary.events.iter() {
+        if let SimulationEventKind::Extinction { species } = &event.kind {
+            if let Some(species) = summary
+                .species
+                .iter_mut()
+                .find(|summary| summary.name == *species && summary.extinction_time.is_none())
+            {
+                species.extinction_time = Some(event.time);
+            }
+        }
+    }
+    summary
+}
+
+/// Runs every config `runs` times on all cores, with the seeds counting up from `settings.seed`.
+/// Every config gets the same seeds. The summaries are grouped by config,
+/// runs that crashed are left out.
+pub fn run_all(
+    configs: &[GenerationConfiguration],
+    runs: u32,
+    settings: &RunSettings,
+) -> Vec<Vec<RunSummary>> {
+    let jobs: Vec<(usize, u32)> = (0..configs.len())
+        .flat_map(|config| (0..runs).map(move |run| (config, run)))
+        .collect();
+    let threads = thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+        .min(jobs.len());
+    println!(
+        "running {} configurations {} times each on {} threads",
+        configs.len(),
+        runs,
+        threads
+    );
+
+    let mut results: Vec<Vec<RunSummary>> = configs.iter().map(|_| Vec::new()).collect();
+    let next_job = AtomicUsize::new(0);
+    let (result_sender, result_receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let result_sender = result_sender.clone();
+            let (jobs, next_job) = (&jobs, &next_job);
+            scope.spawn(move || {
+                while let Some(&(config, run)) = jobs.get(next_job.fetch_add(1, Ordering::SeqCst)) {
+                    let settings = RunSettings {
+                        seed: settings.seed.wrapping_add(run as u64),
+                        ..settings.clone()
+                    };
+                    let summary = panic::catch_unwind(AssertUnwindSafe(|| {
+                        self::run(&configs[config], &settings)
+                    }));
+                    let _ = result_sender.send((config, run, summary.ok()));
+                }
+            });
+        }
+        drop(result_sender);
+
+        let progress_interval = (jobs.len() / 10).max(1);
+        for (finished, (config, run, summary)) in result_receiver.iter().enumerate() {
+            match summary {
+                Some(summary) => results[config].push(summary),
+                None => println!("run {} of configuration {} crashed", run, config),
+            }
+            let finished = finished + 1;
+            if finished % progress_interval == 0 || finished == jobs.len() {
+                println!("finished run {}/{}", finished, jobs.len());
+            }
+        }
+    });
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_the_same_run() {
+        let mut config = crate::generate_default_generation_configuration();
+        for species_config in config.species.iter_mut() {
+            species_config.amount_per_meter *= 0.2;
+        }
+        let settings = RunSettings {
+            seed: 7,
+            duration: Duration::from_secs(20),
+            time_step: Duration::from_millis(50),
+            sample_interval: Duration::from_secs(1),
+            detection: DetectionConfiguration::default(),
+            scenario: Scenario::default(),
+        };
+
+        let first = run(&config, &settings);
+        let second = run(&config, &settings);
+        for (a, b) in first.species.iter().zip(second.species.iter()) {
+            assert_eq!(a.populations, b.populations, "{} differs", a.name);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::configurations::{
+    detection_configuration::DetectionConfiguration,
+    generation_configuration::GenerationConfiguration,
+    optimizer_configuration::OptimizerConfiguration, parameter_bounds::ParameterBounds,
+};
+
+use super::{RunSettings, RunSummary};
+
+/// A set of values for the optimized parameters and how well it did.
+#[derive(Clone)]
+struct Candidate {
+    values: Vec<f32>,
+    /// Mean simulated seconds until the first species went extinct.
+    fitness: f64,
+}
+
+/// Evolves the parameters of the optimizer in `path` towards keeping every species alive
+/// as long as possible, and writes the best configurations found.
+/// Returns the paths of the written configurations, the best first.
+pub fn run_optimizer(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let (optimizer, base_config) = OptimizerConfiguration::load(path)?;
+    let settings = RunSettings {
+        seed: optimizer.seed,
+        duration: optimizer.duration,
+        time_step: optimizer.time_step,
+        sample_interval: optimizer.detection.sample_interval,
+        detection: DetectionConfiguration {
+            stop_on_extinction: true,
+            ..optimizer.detection.clone()
+        },
+        scenario: optimizer.load_scenario(&base_config)?,
+    };
+    let mut rng = StdRng::seed_from_u64(optimizer.seed);
+
+    let base_values: Vec<f32> = optimizer
+        .parameters
+        .iter()
+        .map(|bounds| {
+            bounds
+                .value_in(&base_config)
+                .unwrap_or(bounds.min)
+                .clamp(bounds.min, bounds.max)
+        })
+        .collect();
+    let mut generation: Vec<Vec<f32>> = vec![base_values];
+    while generation.len() < optimizer.population_size as usize {
+        generation.push(
+            optimizer
+                .parameters
+                .iter()
+                .map(|bounds| rng.gen_range(bounds.min..=bounds.max))
+                .collect(),
+        );
+    }
+
+    let mut evaluated: Vec<Candidate> = Vec::new();
+    let mut ranked: Vec<Candidate> = Vec::new();
+    for generation_index in 0..optimizer.generations {
+        // bounds are only checked one at a time, so combinations can still be invalid
+        let configs: Vec<Option<GenerationConfiguration>> = generation
+            .iter()
+            .map(|values| {
+                configuration(&base_config, &optimizer.parameters, values)
+                    .map_err(|error| println!("skipping an invalid candidate: {}", error))
+                    .ok()
+            })
+            .collect();
+        let is_valid: Vec<bool> = configs.iter().map(Option::is_some).collect();
+        let valid_configs: Vec<GenerationConfiguration> = configs.into_iter().flatten().collect();
+        let mut results =
+            super::run_all(&valid_configs, optimizer.runs_per_candidate, &settings).into_iter();
+
+        // the elite was evaluated already, with the same seeds it would get the same fitness
+        ranked.truncate(optimizer.elite as usize);
+        for (values, is_valid) in generation.into_iter().zip(is_valid) {
+            // invalid candidates only compete for breeding, they are never written
+            if !is_valid {
+                ranked.push(Candidate {
+                    values,
+                    fitness: 0.0,
+                });
+                continue;
+            }
+            let runs = results.next().unwrap_or_default();
+            let candidate = Candidate {
+                values,
+                fitness: fitness(&runs, optimizer.runs_per_candidate, &settings),
+            };
+            evaluated.push(candidate.clone());
+            ranked.push(candidate);
+        }
+        sort_by_fitness(&mut ranked);
+
+        let mean = ranked
+            .iter()
+            .map(|candidate| candidate.fitness)
+            .sum::<f64>()
+            / ranked.len() as f64;
+        println!(
+            "generation {}/{}: best {:.1}s, mean {:.1}s until the first extinction",
+            generation_index + 1,
+            optimizer.generations,
+            ranked[0].fitness,
+            mean
+        );
+
+        generation = (optimizer.elite..optimizer.population_size)
+            .map(|_| breed(&ranked, &optimizer, &mut rng))
+            .collect();
+    }
+
+    sort_by_fitness(&mut evaluated);
+    let mut best: Vec<&Candidate> = Vec::new();
+    for candidate in evaluated.iter() {
+        if best.len() == optimizer.best_count as usize {
+            break;
+        }
+        // the elite is evaluated only once, but breeding can still come up with the same values
+        if !best.iter().any(|other| other.values == candidate.values) {
+            best.push(candidate);
+        }
+    }
+
+    fs::create_dir_all(&optimizer.output_directory).map_err(|error| {
+        format!(
+            "creating {} failed: {}",
+            optimizer.output_directory.display(),
+            error
+        )
+    })?;
+
+    let mut written = Vec::new();
+    for (rank, candidate) in best.into_iter().enumerate() {
+        let config = configuration(&base_config, &optimizer.parameters, &candidate.values)?;
+        let path = optimizer
+            .output_directory
+            .join(format!("best_{}.json", rank + 1));
+        config.save(&path)?;
+        let values: Vec<String> = optimizer
+            .parameters
+            .iter()
+            .zip(candidate.values.iter())
+            .map(|(bounds, value)| format!("{} = {}", bounds.name(), value))
+            .collect();
+        println!(
+            "{}: {:.1}s until the first extinction with {}",
+            path.display(),
+            candidate.fitness,
+            values.join(", ")
+        );
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn configuration(
+    base_config: &GenerationConfiguration,
+    parameters: &[ParameterBounds],
+    values: &[f32],
+) -> Result<GenerationConfiguration, String> {
+    let mut config = base_config.clone();
+    for (bounds, value) in parameters.iter().zip(values) {
+        bounds.apply(&mut config, *value)?;
+    }
+    config.validate()?;
+    Ok(config)
+}
+
+/// The mean time until the first extinction. Runs where every species survived
+/// count with their whole duration, runs that crashed count as 0.
+fn fitness(runs: &[RunSummary], runs_per_candidate: u32, settings: &RunSettings) -> f64 {
+    runs.iter()
+        .map(|run| {
+            run.species
+                .iter()
+                .filter_map(|species| species.extinction_time)
+                .min()
+                .unwrap_or(settings.duration)
+                .as_secs_f64()
+        })
+        .sum::<f64>()
+        / runs_per_candidate as f64
+}
+
+fn sort_by_fitness(candidates: &mut [Candidate]) {
+    candidates.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+}
+
+/// A child of two parents picked by tournaments, with every value taken from
+/// one of them at random and then mutated.
+fn breed(ranked: &[Candidate], optimizer: &OptimizerConfiguration, rng: &mut StdRng) -> Vec<f32> {
+    let tournament = |rng: &mut StdRng| {
+        let a = ranked.choose(rng).unwrap();
+        let b = ranked.choose(rng).unwrap();
+        if a.fitness >= b.fitness {
+            a
+        } else {
+            b
+        }
+    };
+    let mother = tournament(rng);
+    let father = tournament(rng);
+
+    optimizer
+        .parameters
+        .iter()
+        .enumerate()
+        .map(|(i, bounds)| {
+            let value = if rng.gen_bool(0.5) {
+                mother.values[i]
+            } else {
+                father.values[i]
+            };
+            let deviation = optimizer.mutation_strength * (bounds.max - bounds.min);
+            (value + deviation * standard_normal(rng)).clamp(bounds.min, bounds.max)
+        })
+        .collect()
+}
+
+/// A normally distributed random number, with the Box-Muller transform.
+fn standard_normal(rng: &mut StdRng) -> f32 {
+    let u1 = 1.0 - rng.gen::<f32>();
+    let u2 = rng.gen::<f32>();
+    (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::configurations::scenario::Scenario;
+
+    use super::*;
+
+    #[test]
+    fn empty_species_gets_no_fitness() {
+        let mut config = crate::generate_default_generation_configuration();
+        for species_config in config.species.iter_mut() {
+            if species_config.species.name == "Scavenger" {
+                species_config.amount_per_meter = 0.0;
+            }
+        }
+        let settings = RunSettings {
+            seed: 3,
+            duration: Duration::from_secs(5),
+            time_step: Duration::from_millis(50),
+            sample_interval: Duration::from_secs(1),
+            detection: DetectionConfiguration {
+                stop_on_extinction: true,
+                ..DetectionConfiguration::default()
+            },
+            scenario: Scenario::default(),
+        };
+
+        let summary = super::super::run(&config, &settings);
+        assert!(!summary.coexistence());
+        assert_eq!(fitness(&[summary], 1, &settings), 0.0);
+    }
+}
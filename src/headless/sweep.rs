@@ -0,0 +1,199 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::configurations::{
+    generation_configuration::GenerationConfiguration,
+    sweep_configuration::{Sampling, SweepConfiguration},
+};
+
+use super::{RunSettings, RunSummary};
+
+/// One set of values for the swept parameters.
+struct Combination {
+    values: Vec<f32>,
+    config: GenerationConfiguration,
+}
+
+/// Runs the sweep in `path` on all cores and writes its summary table.
+/// Returns where the table was written.
+pub fn run_sweep(path: &Path) -> Result<PathBuf, String> {
+    let (sweep, base_config) = SweepConfiguration::load(path)?;
+    let combinations = combinations(&sweep, &base_config)?;
+
+    let configs: Vec<GenerationConfiguration> = combinations
+        .iter()
+        .map(|combination| combination.config.clone())
+        .collect();
+    let settings = RunSettings {
+        seed: sweep.seed,
+        duration: sweep.duration,
+        time_step: sweep.time_step,
+        sample_interval: sweep.sample_interval,
+        detection: sweep.detection.clone(),
+        scenario: sweep.load_scenario(&base_config)?,
+    };
+    let results = super::run_all(&configs, sweep.runs_per_combination, &settings);
+
+    let table = summary_table(&sweep, &base_config, &combinations, &results);
+    fs::write(&sweep.output, table)
+        .map_err(|error| format!("writing {} failed: {}", sweep.output.display(), error))?;
+    Ok(sweep.output)
+}
+
+fn combinations(
+    sweep: &SweepConfiguration,
+    base_config: &GenerationConfiguration,
+) -> Result<Vec<Combination>, String> {
+    let value_sets: Vec<Vec<f32>> = match sweep.sampling {
+        Sampling::Grid => sweep
+            .parameters
+            .iter()
+            .fold(vec![Vec::new()], |sets, range| {
+                sets.iter()
+                    .flat_map(|set| {
+                        range.grid_values().into_iter().map(move |value| {
+                            let mut set = set.clone();
+                            set.push(value);
+                            set
+                        })
+                    })
+                    .collect()
+            }),
+        Sampling::Random { samples } => {
+            let mut rng = StdRng::seed_from_u64(sweep.seed);
+            (0..samples)
+                .map(|_| {
+                    sweep
+                        .parameters
+                        .iter()
+                        .map(|range| rng.gen_range(range.bounds.min..=range.bounds.max))
+                        .collect()
+                })
+                .collect()
+        }
+    };
+
+    value_sets
+        .into_iter()
+        .map(|values| {
+            let mut config = base_config.clone();
+            for (range, value) in sweep.parameters.iter().zip(values.iter()) {
+                range.bounds.apply(&mut config, *value)?;
+            }
+            config
+                .validate()
+                .map_err(|error| format!("combination {:?} is invalid: {}", values, error))?;
+            Ok(Combination { values, config })
+        })
+        .collect()
+}
+
+/// One row per combination with the parameter values, whether all species coexisted
+/// in every run, and per species how often and when on average it went extinct
+/// and the mean and variance of its population over all samples of all runs.
+fn summary_table(
+    sweep: &SweepConfiguration,
+    base_config: &GenerationConfiguration,
+    combinations: &[Combination],
+    results: &[Vec<RunSummary>],
+) -> String {
+    let species_names: Vec<&str> = base_config
+        .species
+        .iter()
+        .map(|species_config| species_config.species.name.as_str())
+        .collect();
+
+    let mut header: Vec<String> = sweep
+        .parameters
+        .iter()
+        .map(|range| range.bounds.name())
+        .collect();
+    header.push(String::from("runs"));
+    header.push(String::from("coexistence"));
+    header.push(String::from("stopped runs"));
+    header.push(String::from("stop reasons"));
+    for name in species_names.iter() {
+        header.push(format!("{} extinct runs", name));
+        header.push(format!("{} mean extinction s", name));
+        header.push(format!("{} population mean", name));
+        header.push(format!("{} population variance", name));
+    }
+
+    let mut table = String::new();
+    write_row(&mut table, &header);
+    for (combination, runs) in combinations.iter().zip(results) {
+        let mut row: Vec<String> = combination
+            .values
+            .iter()
+            .map(|value| value.to_string())
+            .collect();
+        row.push(runs.len().to_string());
+        row.push((!runs.is_empty() && runs.iter().all(RunSummary::coexistence)).to_string());
+        let stop_reasons: Vec<String> = runs
+            .iter()
+            .filter_map(|run| run.stop_reason.as_ref())
+            .map(|event| event.description())
+            .collect();
+        row.push(stop_reasons.len().to_string());
+        row.push(stop_reasons.join("; "));
+
+        for (i, _) in species_names.iter().enumerate() {
+            let extinction_times: Vec<f64> = runs
+                .iter()
+                .filter_map(|run| run.species[i].extinction_time)
+                .map(|time| time.as_secs_f64())
+                .collect();
+            let populations: Vec<f64> = runs
+                .iter()
+                .flat_map(|run| run.species[i].populations.iter())
+                .map(|population| *population as f64)
+                .collect();
+            let (population_mean, population_variance) = mean_and_variance(&populations);
+
+            row.push(extinction_times.len().to_string());
+            row.push(if extinction_times.is_empty() {
+                String::new()
+            } else {
+                format!("{:.2}", mean_and_variance(&extinction_times).0)
+            });
+            row.push(format!("{:.2}", population_mean));
+            row.push(format!("{:.2}", population_variance));
+        }
+        write_row(&mut table, &row);
+    }
+    table
+}
+
+/// The mean and population variance, zeros for no values.
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    (mean, variance)
+}
+
+fn write_row(table: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    let _ = writeln!(table, "{}", fields.join(","));
+}
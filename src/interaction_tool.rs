@@ -0,0 +1,167 @@
+use ggez::mint::Point2;
+
+use crate::{simulation_thread::SimulationData, vector_helper};
+
+const MIN_BRUSH_RADIUS: f32 = 0.5;
+const MAX_BRUSH_RADIUS: f32 = 100.0;
+const BRUSH_RADIUS_FACTOR: f32 = 1.2;
+/// How many organisms a brush spawns per square world unit.
+const SPAWN_DENSITY: f32 = 0.05;
+const MAX_SPAWN_AMOUNT: u32 = 500;
+/// The remove tool reaches at least this far from the cursor, in pixels.
+const MIN_REMOVE_RADIUS_PX: f32 = 12.0;
+
+/// What a left click in the world does.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tool {
+    Select,
+    Spawn,
+    Remove,
+}
+
+impl Tool {
+    pub fn next(self) -> Self {
+        match self {
+            Tool::Select => Tool::Spawn,
+            Tool::Spawn => Tool::Remove,
+            Tool::Remove => Tool::Select,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Select => "select",
+            Tool::Spawn => "spawn",
+            Tool::Remove => "remove",
+        }
+    }
+}
+
+/// The tool used for clicking into the world and its brush.
+/// A brush radius of zero spawns single organisms.
+pub struct InteractionTool {
+    tool: Tool,
+    species_name: Option<String>,
+    brush_radius: f32,
+    /// Where the brush was last applied while the button is held down.
+    last_stamp: Option<Point2<f32>>,
+}
+
+impl InteractionTool {
+    pub fn new() -> Self {
+        Self {
+            tool: Tool::Select,
+            species_name: None,
+            brush_radius: 0.0,
+            last_stamp: None,
+        }
+    }
+
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    pub fn next_tool(&mut self) {
+        self.tool = self.tool.next();
+        self.last_stamp = None;
+    }
+
+    pub fn species_name(&self) -> Option<&str> {
+        self.species_name.as_deref()
+    }
+
+    /// Switches the spawned species to the next configured one,
+    /// including species that died out.
+    pub fn next_species(&mut self, data: &SimulationData) {
+        let names = &data.species_names;
+        let next = match &self.species_name {
+            Some(current) => names
+                .iter()
+                .position(|name| name == current)
+                .and_then(|i| names.get(i + 1))
+                .or(names.first()),
+            None => names.first(),
+        };
+        self.species_name = next.cloned();
+    }
+
+    pub fn brush_radius(&self) -> f32 {
+        self.brush_radius
+    }
+
+    /// Grows or shrinks the brush. Shrinking the smallest brush goes down to single organisms.
+    pub fn change_brush_radius(&mut self, amount: f32) {
+        let radius = if self.brush_radius == 0.0 {
+            if amount <= 0.0 {
+                return;
+            }
+            MIN_BRUSH_RADIUS
+        } else {
+            self.brush_radius * BRUSH_RADIUS_FACTOR.powf(amount)
+        };
+        self.brush_radius = if radius < MIN_BRUSH_RADIUS {
+            0.0
+        } else {
+            radius.min(MAX_BRUSH_RADIUS)
+        };
+    }
+
+    /// The radius in world units the tool works in at the current `zoom`.
+    pub fn effective_radius(&self, zoom: f32) -> f32 {
+        match self.tool {
+            Tool::Remove => self.brush_radius.max(MIN_REMOVE_RADIUS_PX / zoom),
+            Tool::Select | Tool::Spawn => self.brush_radius,
+        }
+    }
+
+    /// How many organisms one use of the spawn brush adds.
+    pub fn spawn_amount(&self) -> u32 {
+        if self.brush_radius == 0.0 {
+            return 1;
+        }
+        let area = std::f32::consts::PI * self.brush_radius * self.brush_radius;
+        ((area * SPAWN_DENSITY).round() as u32).clamp(1, MAX_SPAWN_AMOUNT)
+    }
+
+    /// Whether the brush should be applied at `world_point`.
+    /// While dragging it's only applied again once it moved far enough from the last time.
+    pub fn should_stamp(&self, world_point: Point2<f32>, min_distance: f32) -> bool {
+        match self.last_stamp {
+            Some(last_stamp) => {
+                let spacing = self.brush_radius.max(min_distance);
+                vector_helper::distance(last_stamp, world_point) >= spacing
+            }
+            None => true,
+        }
+    }
+
+    pub fn stamped(&mut self, world_point: Point2<f32>) {
+        self.last_stamp = Some(world_point);
+    }
+
+    pub fn stop_painting(&mut self) {
+        self.last_stamp = None;
+    }
+
+    pub fn is_painting(&self) -> bool {
+        self.last_stamp.is_some()
+    }
+
+    /// A description of the tool, for the HUD.
+    pub fn description(&self) -> String {
+        let brush = if self.brush_radius == 0.0 {
+            String::from("single")
+        } else {
+            format!("brush {:.1}", self.brush_radius)
+        };
+        match self.tool {
+            Tool::Select => String::from("tool: select"),
+            Tool::Spawn => format!(
+                "tool: spawn {} ({})",
+                self.species_name.as_deref().unwrap_or("nothing"),
+                brush
+            ),
+            Tool::Remove => format!("tool: remove ({})", brush),
+        }
+    }
+}
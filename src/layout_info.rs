@@ -78,6 +78,39 @@ impl LayoutInfo {
         Rect { x, y, w, h }
     }
 
+    /// Where a point given in parent coordinates ends up on the screen,
+    /// for a drawable positioned with this layout's anchor.
+    pub fn get_screen_point(
+        &self,
+        point_in_parent: Point2<f32>,
+        parent_screen_rect: &Rect,
+        parent_rect_scale: f32,
+    ) -> Point2<f32> {
+        Point2 {
+            x: point_in_parent.x * parent_rect_scale
+                + parent_screen_rect.x
+                + self.anchor.x * parent_screen_rect.w,
+            y: point_in_parent.y * parent_rect_scale
+                + parent_screen_rect.y
+                + self.anchor.y * parent_screen_rect.h,
+        }
+    }
+
+    /// The inverse of [`LayoutInfo::get_screen_point`].
+    pub fn get_point_in_parent(
+        &self,
+        screen_point: Point2<f32>,
+        parent_screen_rect: &Rect,
+        parent_rect_scale: f32,
+    ) -> Point2<f32> {
+        Point2 {
+            x: (screen_point.x - parent_screen_rect.x - self.anchor.x * parent_screen_rect.w)
+                / parent_rect_scale,
+            y: (screen_point.y - parent_screen_rect.y - self.anchor.y * parent_screen_rect.h)
+                / parent_rect_scale,
+        }
+    }
+
     pub fn new() -> LayoutInfo {
         LayoutInfo {
             raw_rect_in_parent: Rect::new(0., 0., 1., 1.),
@@ -1,29 +1,79 @@
 #![allow(dead_code)]
 
+mod arguments;
+mod camera_follow;
+mod clock;
 mod configurations;
 mod environment;
 mod environment_awareness;
+mod headless;
+mod interaction_tool;
 mod layout_info;
+mod navigation;
+mod nutrient_field;
+mod organism_renderer;
 mod organisms;
+mod overlays;
+mod population_monitor;
+pub mod random;
 pub mod simulation;
+pub mod simulation_event;
 pub mod simulation_thread;
+mod terrain;
+pub mod triple_buffer;
 pub mod vector_helper;
+mod weather;
 
+use std::path::Path;
 use std::time::Duration;
-use std::{env, fs};
 
+use arguments::Arguments;
+use configurations::clock_configuration::ClockConfiguration;
+use configurations::config_watcher::{ConfigWatcher, ReloadMode};
 use configurations::generation_configuration::GenerationConfiguration;
+use configurations::nutrient_configuration::NutrientConfiguration;
+use configurations::scenario::Scenario;
 use configurations::species_generation_configuration::SpeciesGenerationConfiguration;
+use configurations::terrain_configuration::TerrainConfiguration;
+use configurations::weather_configuration::WeatherConfiguration;
 use environment::Environment;
 use ggez::conf::WindowSetup;
 use ggez::event::{self, EventHandler};
 use ggez::graphics::{self, BlendMode, Color, DrawParam, Text};
 
+use ggez::input::keyboard::KeyMods;
+use ggez::input::mouse::MouseButton;
 use ggez::winit::event::VirtualKeyCode;
 use ggez::{Context, ContextBuilder, GameResult};
-use organisms::species::{HuntingBehavior, Nutrition, Species};
+use organisms::species::{
+    Activity, HuntingBehavior, Nutrition, OrganismShape, SizeScaling, Species,
+};
+use overlays::config_editor::{ConfigEditor, ConfigEditorAction};
 
 fn main() {
+    let arguments = match Arguments::from_env() {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        }
+    };
+
+    if let Some(sweep_path) = &arguments.sweep_path {
+        match headless::sweep::run_sweep(sweep_path) {
+            Ok(output) => println!("sweep results written to {}", output.display()),
+            Err(error) => println!("sweep failed: {}", error),
+        }
+        return;
+    }
+    if let Some(optimizer_path) = &arguments.optimizer_path {
+        match headless::optimizer::run_optimizer(optimizer_path) {
+            Ok(written) => println!("{} best configurations written", written.len()),
+            Err(error) => println!("optimizing failed: {}", error),
+        }
+        return;
+    }
+
     // Make a Context.
     let (mut ctx, event_loop) = ContextBuilder::new("my_game", "HoutarouOreki")
         .window_setup(WindowSetup::default().samples(ggez::conf::NumSamples::Four))
@@ -33,7 +83,7 @@ fn main() {
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let my_game = MyGame::new(&mut ctx);
+    let my_game = MyGame::new(&mut ctx, &arguments);
     ctx.gfx.set_window_title("Ecosystem Simulator");
     let _resize_result = ctx.gfx.set_resizable(true);
 
@@ -46,16 +96,33 @@ struct MyGame {
     time_per_step: Duration,
     environment: Environment,
     species_gen_config: GenerationConfiguration,
+    config_watcher: ConfigWatcher,
+    reload_mode: ReloadMode,
+    /// The outcome of the last time the configuration was loaded, and whether it failed.
+    config_status: Option<(String, bool)>,
+    config_editor: ConfigEditor,
     speed: u32,
 }
 
 impl MyGame {
-    pub fn new(ctx: &mut Context) -> MyGame {
-        let species_gen_config = Self::get_generation_config();
+    pub fn new(ctx: &mut Context, arguments: &Arguments) -> MyGame {
+        let (species_gen_config, mut config_status) =
+            Self::get_generation_config(&arguments.config_path);
+        let scenario = match &arguments.scenario_path {
+            Some(path) => Scenario::load(path, &species_gen_config).unwrap_or_else(|error| {
+                config_status = Some((format!("{}\nrunning without a scenario", error), true));
+                Scenario::default()
+            }),
+            None => Scenario::default(),
+        };
         let time_step = Duration::from_secs_f32(0.05);
-        let environment = Environment::new(ctx, time_step, &species_gen_config);
+        let environment = Environment::new(ctx, time_step, &species_gen_config, scenario);
         MyGame {
             species_gen_config,
+            config_watcher: ConfigWatcher::new(arguments.config_path.clone()),
+            reload_mode: ReloadMode::ApplyLive,
+            config_status,
+            config_editor: ConfigEditor::new(),
             time_to_simulate: Duration::ZERO,
             environment,
             time_per_step: time_step,
@@ -73,34 +140,108 @@ impl MyGame {
         }
     }
 
-    fn load_env_generation_config() -> Option<GenerationConfiguration> {
-        if let Some(curr_dir) = env::current_dir().ok()?.to_str() {
-            let assets_dir = curr_dir.to_owned() + "/assets";
-            let config_file = assets_dir + "/default_species_config.json";
-            let json = fs::read_to_string(config_file).ok()?;
-            let config = serde_json::from_str(json.as_str()).ok()?;
-            return config;
+    /// Falls back to the built in configuration if the file can't be used,
+    /// returning the reason as an error status.
+    fn get_generation_config(path: &Path) -> (GenerationConfiguration, Option<(String, bool)>) {
+        match GenerationConfiguration::load(path) {
+            Ok(config) => (config, None),
+            Err(error) => (
+                generate_default_generation_configuration(),
+                Some((format!("{}\nusing the built in configuration", error), true)),
+            ),
         }
-        None
     }
 
-    fn get_generation_config() -> GenerationConfiguration {
-        if let Some(config) = Self::load_env_generation_config() {
-            config
-        } else {
-            println!("Loading species config json failed.");
-            generate_default_generation_configuration()
+    fn restart(&mut self) {
+        let (species_gen_config, config_status) =
+            Self::get_generation_config(self.config_watcher.path());
+        self.config_status = config_status;
+        self.restart_with(species_gen_config);
+    }
+
+    /// Loads the changed configuration file and applies it as [`MyGame::reload_mode`] says.
+    /// An invalid file is reported and otherwise ignored.
+    fn reload_config(&mut self) {
+        let config = match GenerationConfiguration::load(self.config_watcher.path()) {
+            Ok(config) => config,
+            Err(error) => {
+                self.config_status = Some((error, true));
+                return;
+            }
+        };
+
+        match self.reload_mode {
+            ReloadMode::ApplyLive => {
+                let new_species: Vec<&str> = config
+                    .species
+                    .iter()
+                    .map(|species_config| species_config.species.name.as_str())
+                    .filter(|name| {
+                        !self
+                            .species_gen_config
+                            .species
+                            .iter()
+                            .any(|old| old.species.name == *name)
+                    })
+                    .collect();
+                let status = if new_species.is_empty() {
+                    String::from("configuration applied live")
+                } else {
+                    format!(
+                        "configuration applied live, new species need a restart: {}",
+                        new_species.join(", ")
+                    )
+                };
+                self.environment.update_species(
+                    config
+                        .species
+                        .iter()
+                        .map(|species_config| species_config.species.clone())
+                        .collect(),
+                );
+                self.species_gen_config = config;
+                self.config_status = Some((status, false));
+            }
+            ReloadMode::Restart => {
+                self.restart_with(config);
+                self.config_status = Some((String::from("configuration reloaded"), false));
+            }
+            ReloadMode::Off => {}
         }
     }
 
-    fn restart(&mut self) {
-        let species_gen_config = Self::get_generation_config();
+    fn restart_with(&mut self, species_gen_config: GenerationConfiguration) {
         self.species_gen_config = species_gen_config.to_owned();
 
         self.time_to_simulate = Duration::ZERO;
 
         self.environment.restart(species_gen_config);
     }
+
+    fn handle_config_editor_action(&mut self, action: ConfigEditorAction) {
+        match action {
+            ConfigEditorAction::None => {}
+            ConfigEditorAction::ApplyAndRestart(config) => {
+                self.restart_with(config);
+                self.config_editor
+                    .set_status(String::from("applied, not saved"), false);
+            }
+            ConfigEditorAction::SaveAndRestart(config) => {
+                match config.save(self.config_watcher.path()) {
+                    Ok(()) => {
+                        // already applied, no need to reload it
+                        self.config_watcher.mark_seen();
+                        self.restart_with(config);
+                        self.config_editor.set_status(
+                            format!("saved to {}", self.config_watcher.path().display()),
+                            false,
+                        );
+                    }
+                    Err(error) => self.config_editor.set_status(error, true),
+                }
+            }
+        }
+    }
 }
 
 fn generate_default_generation_configuration() -> GenerationConfiguration {
@@ -125,6 +266,9 @@ fn generate_default_generation_configuration() -> GenerationConfiguration {
                     eating_distance: 0.2,
                     max_per_meter: 0.0,
                     hunting_behavior: HuntingBehavior::Random,
+                    shape: OrganismShape::Circle,
+                    size_scaling: SizeScaling::MaxHealth,
+                    activity: Activity::Always,
                 },
                 amount_per_meter: 0.2,
             },
@@ -148,6 +292,9 @@ fn generate_default_generation_configuration() -> GenerationConfiguration {
                     // should increase eating distance to eat corpses
                     max_per_meter: 2.0,
                     hunting_behavior: HuntingBehavior::Random,
+                    shape: OrganismShape::Square,
+                    size_scaling: SizeScaling::Energy,
+                    activity: Activity::Always,
                 },
                 amount_per_meter: 0.6,
             },
@@ -170,6 +317,9 @@ fn generate_default_generation_configuration() -> GenerationConfiguration {
                     eating_distance: 0.2,
                     max_per_meter: 0.0,
                     hunting_behavior: HuntingBehavior::Random,
+                    shape: OrganismShape::Triangle,
+                    size_scaling: SizeScaling::MaxHealth,
+                    activity: Activity::Always,
                 },
                 amount_per_meter: 0.04,
             },
@@ -192,10 +342,17 @@ fn generate_default_generation_configuration() -> GenerationConfiguration {
                     eating_distance: 0.2,
                     max_per_meter: 0.0,
                     hunting_behavior: HuntingBehavior::Random,
+                    shape: OrganismShape::Triangle,
+                    size_scaling: SizeScaling::MaxHealth,
+                    activity: Activity::Always,
                 },
                 amount_per_meter: 0.01,
             },
         ],
+        terrain: TerrainConfiguration::default(),
+        nutrients: NutrientConfiguration::default(),
+        clock: ClockConfiguration::default(),
+        weather: WeatherConfiguration::default(),
     }
 }
 
@@ -207,6 +364,23 @@ impl EventHandler for MyGame {
         _repeated: bool,
     ) -> GameResult {
         let time_per_step_step = Duration::from_secs_f32(0.1);
+
+        if input.keycode == Some(VirtualKeyCode::E) {
+            if input.mods.contains(KeyMods::SHIFT) {
+                self.print_env_generation_config();
+            } else {
+                self.config_editor.toggle(&self.species_gen_config);
+            }
+            return Ok(());
+        }
+        if self.config_editor.is_visible() {
+            if let Some(keycode) = input.keycode {
+                let action = self.config_editor.key_down_event(keycode);
+                self.handle_config_editor_action(action);
+            }
+            return Ok(());
+        }
+
         match input.keycode {
             Some(VirtualKeyCode::Semicolon) => {
                 if let Some(new_value) = self.speed.checked_sub(1) {
@@ -224,13 +398,78 @@ impl EventHandler for MyGame {
                 }
                 self.environment.change_time_step(self.time_per_step);
             }
-            Some(VirtualKeyCode::E) => self.print_env_generation_config(),
+            Some(VirtualKeyCode::Space) => self.environment.toggle_pause(),
+            Some(VirtualKeyCode::N) => self.environment.single_step(1),
             Some(VirtualKeyCode::R) => self.restart(),
+            Some(VirtualKeyCode::F5) => self.reload_mode = self.reload_mode.next(),
             _ => self.environment.key_down_event(_ctx, input, _repeated),
         };
         Ok(())
     }
 
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> GameResult {
+        if button == MouseButton::Left {
+            let (used, action) = self.config_editor.mouse_button_down_event(x, y);
+            self.handle_config_editor_action(action);
+            if used {
+                return Ok(());
+            }
+        }
+        self.environment.mouse_button_down_event(button, x, y);
+        Ok(())
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        if self.config_editor.is_visible() {
+            self.config_editor.text_input_event(character);
+        }
+        Ok(())
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> GameResult {
+        if button == MouseButton::Left {
+            self.config_editor.mouse_button_up_event();
+        }
+        self.environment.mouse_button_up_event(button);
+        Ok(())
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    ) -> GameResult {
+        if self.config_editor.mouse_motion_event(x) {
+            return Ok(());
+        }
+        self.environment.mouse_motion_event(x, y, dx, dy);
+        Ok(())
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+        if ctx.keyboard.active_mods().contains(KeyMods::CTRL) {
+            self.environment.change_brush_radius(y);
+            return Ok(());
+        }
+        self.environment.mouse_wheel_event(ctx.mouse.position(), y);
+        Ok(())
+    }
+
     fn key_up_event(
         &mut self,
         ctx: &mut Context,
@@ -241,7 +480,13 @@ impl EventHandler for MyGame {
     }
 
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        self.time_to_simulate += ctx.time.delta() * self.speed;
+        if self.config_watcher.poll() && self.reload_mode != ReloadMode::Off {
+            self.reload_config();
+        }
+
+        if !self.environment.is_paused() {
+            self.time_to_simulate += ctx.time.delta() * self.speed;
+        }
 
         let steps_to_simulate: u32 =
             (self.time_to_simulate.as_millis() / self.time_per_step.as_millis()) as u32;
@@ -253,28 +498,45 @@ impl EventHandler for MyGame {
         Ok(())
     }
 
+    fn quit_event(&mut self, _ctx: &mut Context) -> Result<bool, ggez::GameError> {
+        self.environment.shutdown();
+        Ok(false)
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         self.environment.handle_camera_controls(ctx);
 
-        let mut canvas = graphics::Canvas::from_frame(ctx, Color::BLACK);
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.environment.background_color());
 
         canvas.set_blend_mode(BlendMode::REPLACE);
         canvas.set_premultiplied_text(false);
 
         self.environment.draw(&mut canvas, ctx);
+        self.config_editor.draw(&mut canvas, ctx);
 
         let (width, _) = ctx.gfx.drawable_size();
 
         canvas.draw(
             &Text::new(format!(
-                "speed: {:.0}x\nstep delta: {}ms\nbehind: {}ms",
+                "speed: {:.0}x\nstep delta: {}ms\nbehind: {}ms\nconfig reload: {}",
                 self.speed,
                 self.time_per_step.as_millis(),
                 self.time_to_simulate.as_millis(),
+                self.reload_mode.name(),
             )),
             DrawParam::default().dest([width - 200.0, 0.0]),
         );
 
+        if let Some((status, is_error)) = &self.config_status {
+            let mut text = Text::new(status.as_str());
+            text.set_bounds([width * 0.4, f32::INFINITY]);
+            let color = if *is_error { Color::RED } else { Color::WHITE };
+            canvas.draw(
+                &text,
+                DrawParam::default().dest([width * 0.3, 0.0]).color(color),
+            );
+        }
+
         canvas.finish(ctx)
     }
 }
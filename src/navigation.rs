@@ -0,0 +1,339 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    sync::Arc,
+};
+
+use ggez::mint::Point2;
+
+use crate::{simulation::BOUNDARY_DISTANCE_FROM_CENTER, terrain::Terrain};
+
+const NAVIGATION_CELL_SIZE: f32 = 2.0;
+/// The cache is emptied when it holds more distance fields than this.
+const MAX_CACHED_FIELDS: usize = 256;
+
+type Cell = (usize, usize);
+/// How far every cell is from one target, infinite when it can't be reached.
+type DistanceField = Arc<Vec<f32>>;
+
+/// Finds ways around impassable terrain over a grid of walkable cells.
+/// The distances of all cells to a target are cached per target cell,
+/// so the way there can be looked up from anywhere.
+pub struct NavigationGrid {
+    size: usize,
+    walkable: Vec<bool>,
+    has_blocked_cells: bool,
+    distances: RefCell<HashMap<Cell, DistanceField>>,
+}
+
+/// An open cell of the Dijkstra search, ordered so that the heap pops the closest first.
+#[derive(PartialEq)]
+struct OpenCell {
+    distance: f32,
+    cell: Cell,
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.cell.cmp(&self.cell))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NavigationGrid {
+    /// A cell is walkable when its center and corners are passable.
+    pub fn new(terrain: &Terrain) -> Self {
+        let size =
+            ((2.0 * BOUNDARY_DISTANCE_FROM_CENTER / NAVIGATION_CELL_SIZE).ceil() as usize).max(1);
+        let mut grid = Self {
+            size,
+            walkable: Vec::with_capacity(size * size),
+            has_blocked_cells: false,
+            distances: RefCell::new(HashMap::new()),
+        };
+        if terrain.is_open() {
+            grid.walkable.resize(size * size, true);
+            return grid;
+        }
+
+        for y in 0..size {
+            for x in 0..size {
+                let center = grid.cell_center((x, y));
+                let half = NAVIGATION_CELL_SIZE * 0.5;
+                let walkable = [
+                    (0.0, 0.0),
+                    (-1.0, -1.0),
+                    (1.0, -1.0),
+                    (-1.0, 1.0),
+                    (1.0, 1.0),
+                ]
+                .into_iter()
+                .all(|(dx, dy)| {
+                    terrain.is_passable(Point2 {
+                        x: center.x + dx * half,
+                        y: center.y + dy * half,
+                    })
+                });
+                grid.has_blocked_cells |= !walkable;
+                grid.walkable.push(walkable);
+            }
+        }
+        grid
+    }
+
+    /// Where to walk straight to next on the way from `from` to `to`.
+    /// None when `to` can't be reached.
+    pub fn next_waypoint(&self, from: Point2<f32>, to: Point2<f32>) -> Option<Point2<f32>> {
+        if !self.has_blocked_cells {
+            return Some(to);
+        }
+        let target = self.cell_at(to);
+        if !self.is_walkable(target) {
+            return None;
+        }
+        if self.has_line_of_sight(from, to) {
+            return Some(to);
+        }
+
+        let start = self.cell_at(from);
+        let path = self.path(start, target)?;
+        // the furthest point of the path that can be walked to straight
+        let mut waypoint = self.cell_center(path[0]);
+        for &cell in path.iter().skip(1) {
+            let center = self.cell_center(cell);
+            if !self.has_line_of_sight(from, center) {
+                break;
+            }
+            waypoint = center;
+        }
+        Some(waypoint)
+    }
+
+    /// The cells from `start` to `target`, following the distance field of the target downhill.
+    fn path(&self, start: Cell, target: Cell) -> Option<Vec<Cell>> {
+        let distances = self.distances_to(target);
+        let index = |cell: Cell| cell.1 * self.size + cell.0;
+        let mut path = vec![start];
+        let mut cell = start;
+        let mut remaining = f32::INFINITY;
+        while cell != target {
+            let (next, distance) = self
+                .neighbours(cell)
+                .map(|(neighbour, step)| (neighbour, step + distances[index(neighbour)]))
+                .min_by(|a, b| a.1.total_cmp(&b.1))?;
+            // every step has to get closer, the start can be blocked and have no distance at all
+            if !distance.is_finite() || distances[index(next)] >= remaining {
+                return None;
+            }
+            remaining = distances[index(next)];
+            path.push(next);
+            cell = next;
+        }
+        Some(path)
+    }
+
+    fn distances_to(&self, target: Cell) -> DistanceField {
+        if let Some(distances) = self.distances.borrow().get(&target) {
+            return distances.clone();
+        }
+        let distances = Arc::new(self.find_distances(target));
+        let mut fields = self.distances.borrow_mut();
+        if fields.len() >= MAX_CACHED_FIELDS {
+            fields.clear();
+        }
+        fields.insert(target, distances.clone());
+        distances
+    }
+
+    /// Dijkstra outwards from the target over the walkable cells. Steps cost the same
+    /// in both directions, so this is also the distance from every cell to the target.
+    fn find_distances(&self, target: Cell) -> Vec<f32> {
+        let index = |cell: Cell| cell.1 * self.size + cell.0;
+        let mut distances = vec![f32::INFINITY; self.size * self.size];
+        let mut open = BinaryHeap::new();
+        distances[index(target)] = 0.0;
+        open.push(OpenCell {
+            distance: 0.0,
+            cell: target,
+        });
+
+        while let Some(OpenCell { distance, cell }) = open.pop() {
+            if distance > distances[index(cell)] {
+                // a shorter way to this cell was found after it was pushed
+                continue;
+            }
+            for (neighbour, step) in self.neighbours(cell) {
+                let new_distance = distance + step;
+                if new_distance < distances[index(neighbour)] {
+                    distances[index(neighbour)] = new_distance;
+                    open.push(OpenCell {
+                        distance: new_distance,
+                        cell: neighbour,
+                    });
+                }
+            }
+        }
+        distances
+    }
+
+    /// The walkable cells around `cell` and the cost of stepping to them.
+    /// Diagonal steps can't cut corners. `cell` itself is allowed to be blocked,
+    /// so that organisms pushed into an obstacle can still walk out of it.
+    fn neighbours(&self, cell: Cell) -> impl Iterator<Item = (Cell, f32)> + '_ {
+        let is_walkable_offset = move |dx: i32, dy: i32| {
+            self.offset(cell, dx, dy)
+                .is_some_and(|neighbour| self.is_walkable(neighbour))
+        };
+        (-1i32..=1)
+            .flat_map(|dy| (-1i32..=1).map(move |dx| (dx, dy)))
+            .filter(move |&(dx, dy)| {
+                let diagonal = dx != 0 && dy != 0;
+                (dx != 0 || dy != 0)
+                    && is_walkable_offset(dx, dy)
+                    && (!diagonal || (is_walkable_offset(dx, 0) && is_walkable_offset(0, dy)))
+            })
+            .map(move |(dx, dy)| {
+                let step = if dx != 0 && dy != 0 {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                (self.offset(cell, dx, dy).unwrap(), step)
+            })
+    }
+
+    /// Whether every cell along the straight line is walkable, apart from the one at `from`.
+    fn has_line_of_sight(&self, from: Point2<f32>, to: Point2<f32>) -> bool {
+        let distance = crate::vector_helper::distance(from, to);
+        let steps = (distance / (NAVIGATION_CELL_SIZE * 0.5)).ceil() as usize;
+        let start = self.cell_at(from);
+        (1..=steps).all(|step| {
+            let t = step as f32 / steps as f32;
+            let cell = self.cell_at(Point2 {
+                x: from.x + (to.x - from.x) * t,
+                y: from.y + (to.y - from.y) * t,
+            });
+            cell == start || self.is_walkable(cell)
+        })
+    }
+
+    fn is_walkable(&self, cell: Cell) -> bool {
+        self.walkable[cell.1 * self.size + cell.0]
+    }
+
+    fn offset(&self, cell: Cell, dx: i32, dy: i32) -> Option<Cell> {
+        let x = cell.0 as i32 + dx;
+        let y = cell.1 as i32 + dy;
+        let range = 0..self.size as i32;
+        (range.contains(&x) && range.contains(&y)).then_some((x as usize, y as usize))
+    }
+
+    /// The cell `point` is in, the closest one at the edge for points outside of the world.
+    fn cell_at(&self, point: Point2<f32>) -> Cell {
+        let to_cell = |coordinate: f32| {
+            (((coordinate + BOUNDARY_DISTANCE_FROM_CENTER) / NAVIGATION_CELL_SIZE).max(0.0)
+                as usize)
+                .min(self.size - 1)
+        };
+        (to_cell(point.x), to_cell(point.y))
+    }
+
+    fn cell_center(&self, cell: Cell) -> Point2<f32> {
+        let to_center = |index: usize| {
+            (index as f32 + 0.5) * NAVIGATION_CELL_SIZE - BOUNDARY_DISTANCE_FROM_CENTER
+        };
+        Point2 {
+            x: to_center(cell.0),
+            y: to_center(cell.1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configurations::terrain_configuration::{Obstacle, TerrainConfiguration};
+
+    use super::*;
+
+    fn grid_with(obstacles: Vec<Obstacle>) -> NavigationGrid {
+        NavigationGrid::new(&Terrain::flat(&TerrainConfiguration {
+            obstacles,
+            ..TerrainConfiguration::default()
+        }))
+    }
+
+    #[test]
+    fn path_goes_around_a_wall() {
+        let grid = grid_with(vec![Obstacle::Rectangle {
+            x: -12.0,
+            y: -30.0,
+            w: 4.0,
+            h: 60.0,
+        }]);
+        let from = Point2 { x: -30.0, y: 0.0 };
+        let to = Point2 { x: 10.0, y: 0.0 };
+        let (start, target) = (grid.cell_at(from), grid.cell_at(to));
+
+        let path = grid.path(start, target).unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&target));
+        for step in path.windows(2) {
+            assert!(grid.is_walkable(step[1]));
+            assert!(step[0].0.abs_diff(step[1].0) <= 1 && step[0].1.abs_diff(step[1].1) <= 1);
+        }
+        // the straight way is 20 cells, around the end of the wall is much longer
+        assert!(path.len() > 30);
+
+        let waypoint = grid.next_waypoint(from, to).unwrap();
+        assert_ne!(waypoint, to);
+    }
+
+    #[test]
+    fn distances_to_a_target_are_shared_by_all_starts() {
+        let grid = grid_with(vec![Obstacle::Rectangle {
+            x: -12.0,
+            y: -30.0,
+            w: 4.0,
+            h: 60.0,
+        }]);
+        let to = Point2 { x: 10.0, y: 0.0 };
+        for from in [
+            Point2 { x: -30.0, y: 0.0 },
+            Point2 { x: -30.0, y: 20.0 },
+            Point2 { x: -20.0, y: -40.0 },
+        ] {
+            let path = grid.path(grid.cell_at(from), grid.cell_at(to)).unwrap();
+            assert_eq!(path.last(), Some(&grid.cell_at(to)));
+            assert!(grid.next_waypoint(from, to).is_some());
+        }
+        assert_eq!(grid.distances.borrow().len(), 1);
+    }
+
+    #[test]
+    fn enclosed_target_is_unreachable() {
+        let wall = |x, y, w, h| Obstacle::Rectangle { x, y, w, h };
+        let grid = grid_with(vec![
+            wall(10.0, 10.0, 24.0, 4.0),
+            wall(10.0, 30.0, 24.0, 4.0),
+            wall(10.0, 10.0, 4.0, 24.0),
+            wall(30.0, 10.0, 4.0, 24.0),
+        ]);
+        let from = Point2 { x: -30.0, y: 0.0 };
+        let to = Point2 { x: 22.0, y: 22.0 };
+
+        assert!(grid.is_walkable(grid.cell_at(to)));
+        assert_eq!(grid.path(grid.cell_at(from), grid.cell_at(to)), None);
+        assert_eq!(grid.next_waypoint(from, to), None);
+    }
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use ggez::mint::Point2;
+
+use crate::{
+    configurations::nutrient_configuration::NutrientConfiguration,
+    simulation::BOUNDARY_DISTANCE_FROM_CENTER,
+};
+
+/// How many nutrients are in the soil, on a grid over the world.
+/// Points outside of the world belong to the closest cell at its edge.
+#[derive(Default)]
+pub struct NutrientField {
+    enabled: bool,
+    config: NutrientConfiguration,
+    size: usize,
+    /// Nutrients in every cell, row by row, starting at the top left.
+    cells: Vec<f32>,
+    /// The cells before a diffusion step, kept to reuse the allocation.
+    scratch: Vec<f32>,
+}
+
+impl Clone for NutrientField {
+    fn clone(&self) -> Self {
+        Self {
+            enabled: self.enabled,
+            config: self.config.clone(),
+            size: self.size,
+            cells: self.cells.clone(),
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Reuses the cells of `self`, snapshots are taken every step.
+    fn clone_from(&mut self, source: &Self) {
+        self.enabled = source.enabled;
+        self.config.clone_from(&source.config);
+        self.size = source.size;
+        self.cells.clone_from(&source.cells);
+    }
+}
+
+impl NutrientField {
+    pub fn new(config: &NutrientConfiguration) -> Self {
+        if !config.enabled {
+            return Self {
+                config: config.clone(),
+                ..Self::default()
+            };
+        }
+        let size =
+            ((2.0 * BOUNDARY_DISTANCE_FROM_CENTER / config.cell_size).ceil() as usize).max(1);
+        let cell_area = config.cell_size * config.cell_size;
+        Self {
+            enabled: true,
+            config: config.clone(),
+            size,
+            cells: vec![config.initial_density * cell_area; size * size],
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Takes the nutrients needed for `energy` from under `position`
+    /// and returns how much of the energy they were enough for.
+    pub fn photosynthesize(&mut self, position: Point2<f32>, energy: f32) -> f32 {
+        if !self.enabled || self.config.per_energy == 0.0 || energy <= 0.0 {
+            return energy;
+        }
+        let needed = energy * self.config.per_energy;
+        let index = self.index(position);
+        let cell = &mut self.cells[index];
+        if *cell >= needed {
+            *cell -= needed;
+            energy
+        } else {
+            let available = std::mem::take(cell);
+            available / self.config.per_energy
+        }
+    }
+
+    /// Gives `fraction` of the nutrients of a corpse to the soil under `position`.
+    pub fn decay_corpse(&mut self, position: Point2<f32>, fraction: f32) {
+        self.deposit(position, self.config.per_corpse * fraction);
+    }
+
+    /// Gives the nutrients of a plant that died of age to the soil under `position`.
+    pub fn decay_plant(&mut self, position: Point2<f32>) {
+        self.deposit(position, self.config.per_dead_plant);
+    }
+
+    fn deposit(&mut self, position: Point2<f32>, amount: f32) {
+        if self.enabled {
+            let index = self.index(position);
+            self.cells[index] += amount;
+        }
+    }
+
+    /// Evens out the nutrients between neighbouring cells. Nothing leaves the world,
+    /// so the total stays the same.
+    pub fn diffuse(&mut self, delta: Duration) {
+        if !self.enabled || self.config.diffusion_rate_s == 0.0 {
+            return;
+        }
+        // more would move more than the difference and make the field oscillate
+        let rate = (self.config.diffusion_rate_s * delta.as_secs_f32()).min(0.25);
+        let size = self.size;
+        self.scratch.clone_from(&self.cells);
+        let old = &self.scratch;
+        for y in 0..size {
+            for x in 0..size {
+                let index = y * size + x;
+                let neighbours = [
+                    (x > 0).then(|| index - 1),
+                    (x + 1 < size).then(|| index + 1),
+                    (y > 0).then(|| index - size),
+                    (y + 1 < size).then(|| index + size),
+                ];
+                for neighbour in neighbours.into_iter().flatten() {
+                    self.cells[index] += rate * (old[neighbour] - old[index]);
+                }
+            }
+        }
+    }
+
+    /// Nutrients per square meter at `position`.
+    pub fn density_at(&self, position: Point2<f32>) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        self.cells[self.index(position)] / (self.config.cell_size * self.config.cell_size)
+    }
+
+    /// All nutrients in the soil.
+    pub fn total(&self) -> f32 {
+        self.cells.iter().sum()
+    }
+
+    fn index(&self, position: Point2<f32>) -> usize {
+        let to_cell = |coordinate: f32| {
+            (((coordinate + BOUNDARY_DISTANCE_FROM_CENTER) / self.config.cell_size).max(0.0)
+                as usize)
+                .min(self.size - 1)
+        };
+        to_cell(position.y) * self.size + to_cell(position.x)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use ggez::{
+    context::Has,
+    graphics::{
+        BlendMode, Canvas, Color, DrawMode, DrawParam, GraphicsContext, InstanceArray, Mesh, Rect,
+    },
+    mint::Point2,
+};
+
+use crate::organisms::{organism_info::OrganismInfo, species::OrganismShape};
+
+/// Outline width relative to the one unit wide meshes.
+const OUTLINE_WIDTH: f32 = 0.12;
+const IMMUNITY_OUTLINE_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 0.8,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MeshStyle {
+    Fill,
+    Outline,
+}
+
+/// Draws organisms with one instance array per shape and style.
+/// Living organisms are filled, corpses are only outlined
+/// and organisms under birth immunity get a light outline on top.
+pub struct OrganismRenderer {
+    meshes: HashMap<(OrganismShape, MeshStyle), Mesh>,
+    instances: HashMap<(OrganismShape, MeshStyle), InstanceArray>,
+    drawn_count: usize,
+}
+
+impl OrganismRenderer {
+    pub fn new(gfx: &impl Has<GraphicsContext>) -> Self {
+        let mut meshes = HashMap::new();
+        let mut instances = HashMap::new();
+        for shape in OrganismShape::ALL {
+            for style in [MeshStyle::Fill, MeshStyle::Outline] {
+                meshes.insert((shape, style), create_mesh(gfx, shape, style));
+                instances.insert((shape, style), InstanceArray::new(gfx, None));
+            }
+        }
+        Self {
+            meshes,
+            instances,
+            drawn_count: 0,
+        }
+    }
+
+    /// How many organisms were drawn last time.
+    pub fn drawn_count(&self) -> usize {
+        self.drawn_count
+    }
+
+    pub fn draw<'a>(
+        &mut self,
+        canvas: &mut Canvas,
+        organism_infos: impl Iterator<Item = &'a OrganismInfo>,
+        environment_screen_rect: &Rect,
+        zoom: f32,
+        visibility_rect: &Rect,
+    ) {
+        for instances in self.instances.values_mut() {
+            instances.clear();
+        }
+        self.drawn_count = 0;
+
+        for info in organism_infos {
+            let Some(draw_param) =
+                info.get_draw_param(environment_screen_rect, zoom, visibility_rect)
+            else {
+                continue;
+            };
+            let shape = info.shared_state().species.shape;
+            self.drawn_count += 1;
+
+            if info.is_corpse() {
+                self.push(shape, MeshStyle::Outline, draw_param);
+                continue;
+            }
+
+            self.push(shape, MeshStyle::Fill, draw_param);
+            if info.is_immune() {
+                self.push(
+                    shape,
+                    MeshStyle::Outline,
+                    draw_param.color(IMMUNITY_OUTLINE_COLOR),
+                );
+            }
+        }
+
+        // the frame is drawn replacing colors, which would ignore the fading with age
+        let blend_mode = canvas.blend_mode();
+        canvas.set_blend_mode(BlendMode::ALPHA);
+        // outlines go on top of the filled shapes
+        for style in [MeshStyle::Fill, MeshStyle::Outline] {
+            for shape in OrganismShape::ALL {
+                let key = (shape, style);
+                canvas.draw_instanced_mesh(
+                    self.meshes[&key].clone(),
+                    &self.instances[&key],
+                    DrawParam::default(),
+                );
+            }
+        }
+        canvas.set_blend_mode(blend_mode);
+    }
+
+    fn push(&mut self, shape: OrganismShape, style: MeshStyle, draw_param: DrawParam) {
+        if let Some(instances) = self.instances.get_mut(&(shape, style)) {
+            instances.push(draw_param);
+        }
+    }
+}
+
+/// Creates a mesh one unit wide, centered on its origin.
+fn create_mesh(gfx: &impl Has<GraphicsContext>, shape: OrganismShape, style: MeshStyle) -> Mesh {
+    let draw_mode = match style {
+        MeshStyle::Fill => DrawMode::fill(),
+        MeshStyle::Outline => DrawMode::stroke(OUTLINE_WIDTH),
+    };
+
+    match shape {
+        OrganismShape::Circle => Mesh::new_circle(
+            gfx,
+            draw_mode,
+            Point2 { x: 0.0, y: 0.0 },
+            0.5,
+            0.01,
+            Color::WHITE,
+        ),
+        OrganismShape::Square => Mesh::new_rectangle(
+            gfx,
+            draw_mode,
+            Rect::new(-0.5, -0.5, 1.0, 1.0),
+            Color::WHITE,
+        ),
+        // pointing to the right, which is a rotation of zero
+        OrganismShape::Triangle => Mesh::new_polygon(
+            gfx,
+            draw_mode,
+            &[
+                Point2 { x: 0.5, y: 0.0 },
+                Point2 { x: -0.5, y: 0.4 },
+                Point2 { x: -0.5, y: -0.4 },
+            ],
+            Color::WHITE,
+        ),
+    }
+    .unwrap()
+}
@@ -1,4 +1,5 @@
 pub mod organism;
+pub mod organism_details;
 pub mod organism_eyesight;
 pub mod organism_result;
 pub mod species;
@@ -11,12 +11,13 @@ use ggez::{
 
 use crate::{
     environment_awareness::EnvironmentAwareness, layout_info::LayoutInfo,
-    organisms::states::organism_state::StateTransition,
+    nutrient_field::NutrientField, organisms::states::organism_state::StateTransition,
 };
 
 use super::{
+    organism_details::OrganismDetails,
     organism_result::OrganismResult,
-    species::{HuntingBehavior, Nutrition, Species},
+    species::{Activity, HuntingBehavior, Nutrition, SizeScaling, Species},
     states::{
         dead_state::DeadState, idle_state::IdleState, organism_state::OrganismState,
         shared_state::SharedState,
@@ -25,8 +26,17 @@ use super::{
 
 static NEXT_ID: AtomicU64 = AtomicU64::new(0);
 
+const BASE_SIZE: f32 = 3.0;
+const MIN_SIZE: f32 = 1.0;
+const MAX_SIZE: f32 = 10.0;
+/// Species with this much max health are drawn at [`BASE_SIZE`] when scaled by max health.
+const REFERENCE_MAX_HEALTH: f32 = 50.0;
+
 pub struct Organism {
     pub id: u64,
+    /// The organism this one was born from, if any.
+    /// For corpses, the organism that died.
+    pub parent_id: Option<u64>,
     pub layout_info: LayoutInfo,
     state: Box<dyn OrganismState>,
     pub shared_state: SharedState,
@@ -88,8 +98,22 @@ impl Organism {
             && self.shared_state.health > 0.0
     }
 
+    /// What happens when this organism dies: plants give their nutrients to the soil,
+    /// corpses disappear and everything else leaves a corpse.
+    pub fn die(&self, nutrients: &mut NutrientField) -> OrganismResult {
+        match self.shared_state.species.contained_nutrition {
+            Nutrition::Corpse => OrganismResult::Disappeared,
+            Nutrition::Plant => {
+                nutrients.decay_plant(self.shared_state.position);
+                OrganismResult::Disappeared
+            }
+            _ => OrganismResult::Died,
+        }
+    }
+
     pub fn new_child(organism: &Organism) -> Self {
         let mut new_child = Organism::new(organism.shared_state.species.clone());
+        new_child.parent_id = Some(organism.id());
         new_child.set_position(organism.shared_state.position);
         new_child
     }
@@ -98,6 +122,7 @@ impl Organism {
         let away_vector =
             vecmath::vec2_scale(away_vector, organism.shared_state.species.birth_distance);
         let mut new_child = Organism::new(organism.shared_state.species.clone());
+        new_child.parent_id = Some(organism.id());
         new_child.set_position(
             vecmath::vec2_add(organism.shared_state.position.into(), away_vector).into(),
         );
@@ -111,16 +136,18 @@ impl Organism {
     }
 
     pub fn new(species: Species) -> Self {
-        NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        // one atomic operation, simulations on other threads create organisms at the same time
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst) + 1;
 
         let mut layout_info = LayoutInfo::new();
-        layout_info.raw_rect_in_parent.w = 0.5;
-        layout_info.raw_rect_in_parent.h = 0.5;
+        layout_info.raw_rect_in_parent.w = BASE_SIZE;
+        layout_info.raw_rect_in_parent.h = BASE_SIZE;
 
         let shared_state = SharedState::new_default(species);
 
         Self {
-            id: NEXT_ID.load(Ordering::SeqCst),
+            id,
+            parent_id: None,
             layout_info,
             shared_state,
             state: Box::new(IdleState::new()),
@@ -146,8 +173,12 @@ impl Organism {
             eating_distance: 0.2,
             max_per_meter: 0.0,
             hunting_behavior: HuntingBehavior::Closest,
+            shape: organism.shared_state.species.shape,
+            size_scaling: SizeScaling::Fixed,
+            activity: Activity::Always,
         });
         s.shared_state.position = organism.position();
+        s.parent_id = Some(organism.id());
         s.state = DeadState::new_boxed();
         s
     }
@@ -160,24 +191,42 @@ impl Organism {
         &self.shared_state
     }
 
+    /// Photosynthesis draws from `nutrients`, decaying corpses and plants give back to it.
     pub fn simulate(
         &mut self,
         delta: Duration,
         environment_awareness: &EnvironmentAwareness,
+        nutrients: &mut NutrientField,
     ) -> OrganismResult {
+        let position = self.shared_state.position;
         if self.is_dead() {
-            if self.shared_state.species.contained_nutrition == Nutrition::Corpse
-                || self.shared_state.species.contained_nutrition == Nutrition::Plant
-            {
-                return OrganismResult::Disappeared;
-            } else {
-                return OrganismResult::Died;
+            return self.die(nutrients);
+        }
+        if self.shared_state.species.contained_nutrition == Nutrition::Corpse {
+            let max_age = self.shared_state.species.max_age.as_secs_f32();
+            if max_age > 0.0 {
+                nutrients.decay_corpse(position, delta.as_secs_f32() / max_age);
             }
         }
 
-        self.shared_state
-            .increase_energy(self.shared_state.species.photosynthesis_rate_s * delta.as_secs_f32());
-
+        let clock = environment_awareness.clock();
+        let photosynthesis = environment_awareness
+            .terrain()
+            .properties_at(position)
+            .photosynthesis
+            * clock.light
+            * clock.season_factor
+            * environment_awareness.weather_photosynthesis();
+        // only the energy that fits uses up nutrients
+        let missing_energy = self.shared_state.species.max_energy - self.shared_state.energy();
+        let energy = (self.shared_state.species.photosynthesis_rate_s
+            * photosynthesis
+            * delta.as_secs_f32())
+        .min(missing_energy);
+        let energy = nutrients.photosynthesize(position, energy);
+        self.shared_state.increase_energy(energy);
+
+        let previous_position = self.shared_state.position;
         let state_run_result = self
             .state
             .run(&mut self.shared_state, delta, environment_awareness);
@@ -185,12 +234,19 @@ impl Organism {
             self.state = next_state;
         }
 
+        let movement =
+            vecmath::vec2_sub(self.shared_state.position.into(), previous_position.into());
+        if movement != [0.0, 0.0] {
+            self.shared_state.heading = movement[1].atan2(movement[0]);
+        }
+
+        let size = self.calculate_size();
         self.layout_info = LayoutInfo {
             raw_rect_in_parent: Rect {
                 x: self.shared_state.position.x,
                 y: self.shared_state.position.y,
-                w: 0.3,
-                h: 0.3,
+                w: size,
+                h: size,
             },
             anchor: Point2 { x: 0.5, y: 0.5 },
             origin: Point2 { x: 0.5, y: 0.5 },
@@ -203,6 +259,20 @@ impl Organism {
         state_run_result.organism_result
     }
 
+    /// The size of the organism in world units, depending on its species' [`SizeScaling`].
+    fn calculate_size(&self) -> f32 {
+        let species = &self.shared_state.species;
+        let multiplier = match species.size_scaling {
+            SizeScaling::Fixed => 1.0,
+            SizeScaling::Energy if species.max_energy > 0.0 => {
+                0.5 + 0.5 * (self.shared_state.energy() / species.max_energy).clamp(0.0, 1.0)
+            }
+            SizeScaling::Energy => 1.0,
+            SizeScaling::MaxHealth => (species.max_health / REFERENCE_MAX_HEALTH).max(0.0).sqrt(),
+        };
+        (BASE_SIZE * multiplier).clamp(MIN_SIZE, MAX_SIZE)
+    }
+
     pub fn set_position(&mut self, position: Point2<f32>) {
         self.shared_state.position = position;
     }
@@ -211,6 +281,31 @@ impl Organism {
         self.shared_state.position = Point2 { x, y };
     }
 
+    /// The id and position of the organism being hunted, if any.
+    pub fn hunting_target(&self) -> Option<(u64, Point2<f32>)> {
+        self.state.hunting_target()
+    }
+
+    pub fn details(&self) -> OrganismDetails {
+        let state = self.state.as_ref();
+        OrganismDetails {
+            id: self.id,
+            parent_id: self.parent_id,
+            species_name: self.shared_state.species.name.clone(),
+            position: self.shared_state.position,
+            state_name: state.name(&self.shared_state),
+            state_progress: state.progress(),
+            hunting_target_id: state.hunting_target().map(|(id, _)| id),
+            energy: self.shared_state.energy(),
+            max_energy: self.shared_state.species.max_energy,
+            health: self.shared_state.health,
+            max_health: self.shared_state.species.max_health,
+            age: self.shared_state.age(),
+            max_age: self.shared_state.species.max_age,
+            info_text: Self::get_info_text(state, &self.shared_state),
+        }
+    }
+
     pub fn get_info_text(state: &dyn OrganismState, shared_state: &SharedState) -> String {
         let s = format!(
             "{}\r\nage: {}/{}",
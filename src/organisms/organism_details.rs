@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use ggez::mint::Point2;
+
+/// Everything known about a single organism, including its state machine,
+/// which isn't part of the snapshots sent to the UI.
+#[derive(Clone)]
+pub struct OrganismDetails {
+    pub id: u64,
+    pub parent_id: Option<u64>,
+    pub species_name: String,
+    pub position: Point2<f32>,
+    pub state_name: String,
+    pub state_progress: Option<f32>,
+    pub hunting_target_id: Option<u64>,
+    pub energy: f32,
+    pub max_energy: f32,
+    pub health: f32,
+    pub max_health: f32,
+    pub age: Duration,
+    pub max_age: Duration,
+    pub info_text: String,
+}
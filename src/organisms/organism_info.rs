@@ -1,26 +1,63 @@
-use ggez::graphics::{DrawParam, Rect};
+use ggez::{
+    graphics::{DrawParam, Rect},
+    mint::Point2,
+};
 
 use crate::layout_info::LayoutInfo;
 
-use super::{organism::Organism, states::shared_state::SharedState};
+use super::{
+    organism::Organism,
+    species::{Nutrition, OrganismShape},
+    states::shared_state::SharedState,
+};
+
+/// How transparent organisms get at the end of their life.
+const MIN_ALPHA: f32 = 0.35;
 
 #[derive(Clone)]
 pub struct OrganismInfo {
+    id: u64,
+    parent_id: Option<u64>,
     layout_info: LayoutInfo,
     shared_state: SharedState,
+    hunting_target: Option<Point2<f32>>,
 }
 
 impl OrganismInfo {
     pub fn new(organism: &Organism) -> Self {
         OrganismInfo {
+            id: organism.id(),
+            parent_id: organism.parent_id,
             layout_info: organism.layout_info,
             shared_state: organism.shared_state.to_owned(),
+            hunting_target: organism.hunting_target().map(|(_, position)| position),
         }
     }
 
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<u64> {
+        self.parent_id
+    }
+
+    pub fn shared_state(&self) -> &SharedState {
+        &self.shared_state
+    }
+
+    /// Where the organism is heading to catch its prey.
+    pub fn hunting_target(&self) -> Option<Point2<f32>> {
+        self.hunting_target
+    }
+
+    /// The width of the organism in world units.
+    pub fn get_world_size(&self) -> f32 {
+        self.layout_info.raw_rect_in_parent.w
+    }
+
     pub fn new_from_organisms(organisms: &Vec<Organism>) -> Vec<OrganismInfo> {
-        let mut vec = Vec::new();
-        vec.reserve(organisms.len());
+        let mut vec = Vec::with_capacity(organisms.len());
         for organism in organisms {
             vec.push(OrganismInfo::new(organism));
         }
@@ -49,10 +86,38 @@ impl OrganismInfo {
             return Option::None;
         }
 
+        let species = &self.shared_state.species;
+        let rotation = if species.shape == OrganismShape::Triangle {
+            self.shared_state.heading
+        } else {
+            0.0
+        };
+
+        // organisms fade as they get older
+        let age_fraction = if species.max_age.is_zero() {
+            0.0
+        } else {
+            self.shared_state.age().as_secs_f32() / species.max_age.as_secs_f32()
+        };
+        let mut color = species.color;
+        color.a *= 1.0 - (1.0 - MIN_ALPHA) * age_fraction.clamp(0.0, 1.0);
+
+        // meshes are one unit wide and centered on their origin
         Some(
             DrawParam::default()
-                .dest_rect(screen_rect)
-                .color(self.shared_state.species.color),
+                .dest(screen_rect.center())
+                .scale([screen_rect.w, screen_rect.h])
+                .rotation(rotation)
+                .color(color),
         )
     }
+
+    pub fn is_corpse(&self) -> bool {
+        self.shared_state.species.contained_nutrition == Nutrition::Corpse
+    }
+
+    /// Whether the organism is still too young to be eaten.
+    pub fn is_immune(&self) -> bool {
+        self.shared_state.species.birth_immunity >= self.shared_state.age()
+    }
 }
@@ -3,6 +3,26 @@ use std::time::Duration;
 use ggez::graphics::Color;
 use serde::{Deserialize, Serialize};
 
+/// Names of the numeric parameters of a species that can be read and changed by name.
+/// Durations are in seconds.
+pub const SPECIES_NUMERIC_PARAMETERS: [&str; 12] = [
+    "max_energy",
+    "max_health",
+    "max_age",
+    "energy_cost_of_birth",
+    "health_cost_of_birth",
+    "walk_speed_s",
+    "photosynthesis_rate_s",
+    "eyesight_distance",
+    "birth_distance",
+    "birth_immunity",
+    "eating_distance",
+    "max_per_meter",
+];
+
+/// The longest `max_age` and `birth_immunity` in seconds, about eleven days.
+pub const MAX_DURATION_S: f32 = 1_000_000.0;
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Species {
     pub name: String,
@@ -22,6 +42,67 @@ pub struct Species {
     pub eating_distance: f32,
     pub max_per_meter: f32,
     pub hunting_behavior: HuntingBehavior,
+    #[serde(default)]
+    pub shape: OrganismShape,
+    #[serde(default)]
+    pub size_scaling: SizeScaling,
+    #[serde(default)]
+    pub activity: Activity,
+}
+
+impl Species {
+    /// The value of one of [`SPECIES_NUMERIC_PARAMETERS`], None for unknown names.
+    pub fn parameter(&self, name: &str) -> Option<f32> {
+        let value = match name {
+            "max_energy" => self.max_energy,
+            "max_health" => self.max_health,
+            "max_age" => self.max_age.as_secs_f32(),
+            "energy_cost_of_birth" => self.energy_cost_of_birth,
+            "health_cost_of_birth" => self.health_cost_of_birth,
+            "walk_speed_s" => self.walk_speed_s,
+            "photosynthesis_rate_s" => self.photosynthesis_rate_s,
+            "eyesight_distance" => self.eyesight_distance,
+            "birth_distance" => self.birth_distance,
+            "birth_immunity" => self.birth_immunity.as_secs_f32(),
+            "eating_distance" => self.eating_distance,
+            "max_per_meter" => self.max_per_meter,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Changes one of [`SPECIES_NUMERIC_PARAMETERS`].
+    /// None of them make sense negative, so negative values are refused.
+    pub fn set_parameter(&mut self, name: &str, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("{} can't be {}", name, value));
+        }
+
+        match name {
+            "max_energy" => self.max_energy = value,
+            "max_health" => self.max_health = value,
+            "max_age" => self.max_age = duration_from_secs(name, value)?,
+            "energy_cost_of_birth" => self.energy_cost_of_birth = value,
+            "health_cost_of_birth" => self.health_cost_of_birth = value,
+            "walk_speed_s" => self.walk_speed_s = value,
+            "photosynthesis_rate_s" => self.photosynthesis_rate_s = value,
+            "eyesight_distance" => self.eyesight_distance = value,
+            "birth_distance" => self.birth_distance = value,
+            "birth_immunity" => self.birth_immunity = duration_from_secs(name, value)?,
+            "eating_distance" => self.eating_distance = value,
+            "max_per_meter" => self.max_per_meter = value,
+            _ => return Err(format!("unknown parameter {}", name)),
+        }
+        Ok(())
+    }
+}
+
+fn duration_from_secs(name: &str, value: f32) -> Result<Duration, String> {
+    if value > MAX_DURATION_S {
+        return Err(format!("{} can't be longer than {}s", name, MAX_DURATION_S));
+    }
+    Duration::try_from_secs_f32(value)
+        .map_err(|error| format!("{} can't be {}: {}", name, value, error))
 }
 
 #[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -32,8 +113,112 @@ pub enum Nutrition {
     Corpse,
 }
 
+impl Nutrition {
+    pub const ALL: [Nutrition; 4] = [
+        Nutrition::None,
+        Nutrition::Plant,
+        Nutrition::Meat,
+        Nutrition::Corpse,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Nutrition::None => "None",
+            Nutrition::Plant => "Plant",
+            Nutrition::Meat => "Meat",
+            Nutrition::Corpse => "Corpse",
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum HuntingBehavior {
     Closest,
     Random,
 }
+
+impl HuntingBehavior {
+    pub const ALL: [HuntingBehavior; 2] = [HuntingBehavior::Closest, HuntingBehavior::Random];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HuntingBehavior::Closest => "Closest",
+            HuntingBehavior::Random => "Random",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum OrganismShape {
+    #[default]
+    Circle,
+    Square,
+    /// Points in the direction the organism last moved in.
+    Triangle,
+}
+
+impl OrganismShape {
+    pub const ALL: [OrganismShape; 3] = [
+        OrganismShape::Circle,
+        OrganismShape::Square,
+        OrganismShape::Triangle,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OrganismShape::Circle => "Circle",
+            OrganismShape::Square => "Square",
+            OrganismShape::Triangle => "Triangle",
+        }
+    }
+}
+
+/// What the drawn size of an organism depends on.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SizeScaling {
+    Fixed,
+    /// Grows as the organism gains energy.
+    Energy,
+    /// Sturdier species are bigger.
+    #[default]
+    MaxHealth,
+}
+
+impl SizeScaling {
+    pub const ALL: [SizeScaling; 3] = [
+        SizeScaling::Fixed,
+        SizeScaling::Energy,
+        SizeScaling::MaxHealth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SizeScaling::Fixed => "Fixed",
+            SizeScaling::Energy => "Energy",
+            SizeScaling::MaxHealth => "MaxHealth",
+        }
+    }
+}
+
+/// When a species is active, which only matters with the clock turned on.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Activity {
+    #[default]
+    Always,
+    /// Mostly idles at night.
+    Diurnal,
+    /// Mostly idles at day.
+    Nocturnal,
+}
+
+impl Activity {
+    pub const ALL: [Activity; 3] = [Activity::Always, Activity::Diurnal, Activity::Nocturnal];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Activity::Always => "Always",
+            Activity::Diurnal => "Diurnal",
+            Activity::Nocturnal => "Nocturnal",
+        }
+    }
+}
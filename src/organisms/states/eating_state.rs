@@ -41,9 +41,10 @@ impl OrganismState for EatingState {
     }
 
     fn name(&self, _shared_state: &SharedState) -> String {
-        format!(
-            "eating ({:.0}%)",
-            100.0 * (EATING_DURATION_S - self.time_remaining.as_secs_f32()) / EATING_DURATION_S
-        )
+        format!("eating ({:.0}%)", 100.0 * self.progress().unwrap_or(0.0))
+    }
+
+    fn progress(&self) -> Option<f32> {
+        Some((EATING_DURATION_S - self.time_remaining.as_secs_f32()) / EATING_DURATION_S)
     }
 }
@@ -6,6 +6,8 @@ use rand::seq::IteratorRandom;
 use crate::{
     environment_awareness::EnvironmentAwareness,
     organisms::{organism_result::OrganismResult, species::HuntingBehavior},
+    random,
+    terrain::Terrain,
     vector_helper,
 };
 
@@ -31,7 +33,7 @@ impl HuntingState {
         let foreigners_in_radius = get_foreigners_in_eyesight(environment_awareness, shared_state);
 
         if shared_state.species.hunting_behavior == HuntingBehavior::Random {
-            closest = foreigners_in_radius.choose(&mut rand::thread_rng()).cloned();
+            closest = foreigners_in_radius.choose(&mut random::rng()).cloned();
         } else {
             for foreigner_info in foreigners_in_radius {
                 if foreigner_info.species_name == shared_state.species.name
@@ -111,7 +113,23 @@ impl HuntingState {
                 state_transition: StateTransition::Next(EatingState::init_boxed(shared_state)),
             }
         } else {
-            calculate_and_set_position(shared_state, delta, hunted_position);
+            let old_position = shared_state.position;
+            let Some(waypoint) = environment_awareness
+                .navigation()
+                .next_waypoint(old_position, hunted_position)
+            else {
+                return StateRunResult::none_next(WalkingState::init_boxed(shared_state));
+            };
+            calculate_and_set_position(
+                shared_state,
+                delta,
+                waypoint,
+                environment_awareness.terrain(),
+            );
+            if shared_state.position == old_position {
+                // the terrain is in the way, look elsewhere
+                return StateRunResult::none_next(WalkingState::init_boxed(shared_state));
+            }
             StateRunResult::none_same()
         }
     }
@@ -121,10 +139,14 @@ fn get_foreigners_in_eyesight<'a>(
     environment_awareness: &'a EnvironmentAwareness,
     shared_state: &SharedState,
 ) -> impl Iterator<Item = &'a ForeignerInfo> {
-    environment_awareness.get_radius_around(
-        shared_state.position,
-        shared_state.species.eyesight_distance,
-    )
+    let position = shared_state.position;
+    environment_awareness
+        .get_radius_around(position, shared_state.species.eyesight_distance)
+        .filter(move |foreigner| {
+            !environment_awareness
+                .terrain()
+                .blocks_sight(position, foreigner.position)
+        })
 }
 
 impl OrganismState for HuntingState {
@@ -168,6 +190,10 @@ impl OrganismState for HuntingState {
         "hunting".into()
     }
 
+    fn hunting_target(&self) -> Option<(u64, Point2<f32>)> {
+        self.hunted_organism_id_position
+    }
+
     fn init_boxed(shared_state: &mut super::shared_state::SharedState) -> Box<Self>
     where
         Self: Sized,
@@ -180,11 +206,13 @@ fn calculate_and_set_position(
     shared_state: &mut SharedState,
     delta: Duration,
     target_position: Point2<f32>,
+    terrain: &Terrain,
 ) {
     shared_state.position = WalkingState::calculate_position(
         delta,
         shared_state.position,
         target_position,
         shared_state.species.walk_speed_s,
+        terrain,
     );
 }
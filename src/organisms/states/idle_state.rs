@@ -7,7 +7,10 @@ const HUNT_CHANCE: u32 = 24;
 const WALK_CHANCE: u32 = 4;
 const REPRODUCE_CHANCE: u32 = 54;
 
-use crate::environment_awareness::EnvironmentAwareness;
+use crate::{
+    clock::ClockReading, environment_awareness::EnvironmentAwareness,
+    organisms::species::Nutrition, random,
+};
 
 use super::{
     hunting_state::HuntingState,
@@ -30,7 +33,7 @@ impl IdleState {
         Self {
             duration: Duration::ZERO,
             target_duration: Duration::from_secs_f32(
-                rand::thread_rng().gen_range(IDLE_TIME_S[0]..=IDLE_TIME_S[1]),
+                random::rng().gen_range(IDLE_TIME_S[0]..=IDLE_TIME_S[1]),
             ),
         }
     }
@@ -57,7 +60,14 @@ impl IdleState {
 
     fn pick_new_state(
         shared_state: &SharedState,
+        clock: &ClockReading,
     ) -> fn(&mut SharedState) -> Box<dyn OrganismState> {
+        let activity_chance = clock.activity_chance(shared_state.species.activity);
+        if !random::rng().gen_bool(activity_chance as f64) {
+            // not its time of day, so it keeps resting
+            return |st| IdleState::init_boxed(st);
+        }
+
         let total_chance = &mut Self::total_chance(shared_state);
 
         if shared_state.can_walk() && ratio(WALK_CHANCE, total_chance) {
@@ -79,7 +89,7 @@ impl IdleState {
 pub fn ratio(numerator: u32, denominator: &mut u32) -> bool {
     if *denominator == 0 {
         false
-    } else if rand::thread_rng().gen_ratio(numerator, *denominator) {
+    } else if random::rng().gen_ratio(numerator, *denominator) {
         true
     } else {
         *denominator -= numerator;
@@ -96,20 +106,27 @@ impl OrganismState for IdleState {
         &mut self,
         shared_state: &mut SharedState,
         delta: Duration,
-        _environment_awareness: &EnvironmentAwareness,
+        environment_awareness: &EnvironmentAwareness,
     ) -> StateRunResult {
-        self.duration += delta;
+        let clock = environment_awareness.clock();
+        // plants are done idling and try to reproduce sooner in good seasons
+        self.duration += if shared_state.species.contained_nutrition == Nutrition::Plant {
+            delta.mul_f32(clock.season_factor)
+        } else {
+            delta
+        };
         if self.duration >= self.target_duration {
-            StateRunResult::none_next(Self::pick_new_state(shared_state)(shared_state))
+            StateRunResult::none_next(Self::pick_new_state(shared_state, clock)(shared_state))
         } else {
             StateRunResult::none_same()
         }
     }
 
     fn name(&self, _shared_state: &SharedState) -> String {
-        format!(
-            "idling ({:.0}%)",
-            self.duration.as_secs_f32() / self.target_duration.as_secs_f32() * 100.0
-        )
+        format!("idling ({:.0}%)", 100.0 * self.progress().unwrap_or(0.0))
+    }
+
+    fn progress(&self) -> Option<f32> {
+        Some(self.duration.as_secs_f32() / self.target_duration.as_secs_f32())
     }
 }
@@ -29,6 +29,16 @@ pub trait OrganismState {
     }
 
     fn name(&self, shared_state: &SharedState) -> String;
+
+    /// How far along a state with a set duration is, from 0.0 to 1.0.
+    fn progress(&self) -> Option<f32> {
+        None
+    }
+
+    /// The id and last known position of the organism being hunted.
+    fn hunting_target(&self) -> Option<(u64, Point2<f32>)> {
+        None
+    }
 }
 
 pub enum StateTransition {
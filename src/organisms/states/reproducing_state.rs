@@ -8,6 +8,7 @@ use crate::{
         organism_result::OrganismResult,
         states::{idle_state::IdleState, organism_state::StateTransition},
     },
+    random,
 };
 
 use super::{
@@ -41,7 +42,7 @@ impl OrganismState for ReproducingState {
             shared_state.on_had_children();
             return StateRunResult {
                 organism_result: OrganismResult::HadChildren {
-                    amount: rand::thread_rng().gen_range(1..=2),
+                    amount: random::rng().gen_range(1..=2),
                 },
                 state_transition: StateTransition::Next(Box::new(IdleState::initialize(
                     shared_state,
@@ -56,8 +57,11 @@ impl OrganismState for ReproducingState {
     fn name(&self, _shared_state: &SharedState) -> String {
         format!(
             "reproducing ({:.0}%)",
-            100.0 * (REPRODUCING_DURATION_S - self.time_left.as_secs_f32())
-                / REPRODUCING_DURATION_S
+            100.0 * self.progress().unwrap_or(0.0)
         )
     }
+
+    fn progress(&self) -> Option<f32> {
+        Some((REPRODUCING_DURATION_S - self.time_left.as_secs_f32()) / REPRODUCING_DURATION_S)
+    }
 }
@@ -2,12 +2,18 @@ use core::f32;
 use std::time::Duration;
 
 use ggez::mint::Point2;
+use rand::Rng;
 
-use crate::organisms::species::{Nutrition, Species};
+use crate::{
+    organisms::species::{Nutrition, Species},
+    random,
+};
 
 #[derive(Clone)]
 pub struct SharedState {
     pub position: Point2<f32>,
+    /// The direction of the last movement, in radians.
+    pub heading: f32,
     age: Duration,
     energy: f32,
     pub health: f32,
@@ -18,6 +24,7 @@ impl SharedState {
     pub fn new_default(species: Species) -> Self {
         Self {
             position: Point2 { x: 0.0, y: 0.0 },
+            heading: 0.0,
             energy: 0.0,
             health: species.max_health,
             species,
@@ -26,10 +33,12 @@ impl SharedState {
     }
 
     pub fn new_random(species: Species) -> Self {
-        let age = Duration::from_secs_f32(rand::random::<f32>() * species.max_age.as_secs_f32());
+        let age =
+            Duration::from_secs_f32(random::rng().gen::<f32>() * species.max_age.as_secs_f32());
         Self {
             position: Point2 { x: 0.0, y: 0.0 },
-            energy: rand::random::<f32>() * species.max_energy,
+            heading: 0.0,
+            energy: random::rng().gen::<f32>() * species.max_energy,
             health: species.max_health,
             species,
             age,
@@ -45,6 +54,7 @@ impl SharedState {
     ) -> Self {
         Self {
             position,
+            heading: 0.0,
             energy,
             health,
             species,
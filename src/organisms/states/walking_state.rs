@@ -3,7 +3,7 @@ use std::time::Duration;
 use ggez::mint::Point2;
 use rand::Rng;
 
-use crate::{environment_awareness::EnvironmentAwareness, vector_helper};
+use crate::{environment_awareness::EnvironmentAwareness, random, terrain::Terrain, vector_helper};
 
 use super::{
     idle_state::IdleState,
@@ -24,22 +24,33 @@ impl WalkingState {
         vecmath::vec2_len(to_target)
     }
 
+    /// Moves towards `target` as fast as the terrain under `current_pos` allows.
+    /// Doesn't move at all when the step would end on impassable terrain,
+    /// unless the organism is already stuck on it.
     pub fn calculate_position(
         delta: Duration,
         current_pos: Point2<f32>,
         target: Point2<f32>,
         walking_speed_s: f32,
+        terrain: &Terrain,
     ) -> Point2<f32> {
         let to_target = vecmath::vec2_sub(target.into(), current_pos.into());
         let distance = vecmath::vec2_len(to_target);
+        let walking_speed_s = walking_speed_s * terrain.properties_at(current_pos).walk_speed;
 
-        if distance <= walking_speed_s * delta.as_secs_f32() {
+        let new_pos = if distance <= walking_speed_s * delta.as_secs_f32() {
             target
         } else {
             let direction_to_target = vecmath::vec2_normalized(to_target);
             let direction_to_target_per_time =
                 vecmath::vec2_scale(direction_to_target, walking_speed_s * delta.as_secs_f32());
             vecmath::vec2_add(current_pos.into(), direction_to_target_per_time).into()
+        };
+
+        if !terrain.is_passable(new_pos) && terrain.is_passable(current_pos) {
+            current_pos
+        } else {
+            new_pos
         }
     }
 }
@@ -55,17 +66,27 @@ impl OrganismState for WalkingState {
         &mut self,
         shared_state: &mut SharedState,
         delta: Duration,
-        _environment_awareness: &EnvironmentAwareness,
+        environment_awareness: &EnvironmentAwareness,
     ) -> StateRunResult {
+        let old_pos = shared_state.position;
+        let Some(waypoint) = environment_awareness
+            .navigation()
+            .next_waypoint(old_pos, self.target)
+        else {
+            // the target can't be reached
+            return StateRunResult::none_next(Box::new(IdleState::initialize(shared_state)));
+        };
         let new_pos = Self::calculate_position(
             delta,
-            shared_state.position,
-            self.target,
+            old_pos,
+            waypoint,
             shared_state.species.walk_speed_s,
+            environment_awareness.terrain(),
         );
         shared_state.position = new_pos;
 
-        if new_pos.eq(&self.target) {
+        // reached the target or the way there is blocked
+        if new_pos.eq(&self.target) || new_pos.eq(&old_pos) {
             return StateRunResult::none_next(Box::new(IdleState::initialize(shared_state)));
         }
 
@@ -78,9 +99,8 @@ impl OrganismState for WalkingState {
 }
 
 fn pick_random_target(current_pos: Point2<f32>) -> Point2<f32> {
-    let distance: f32 =
-        rand::thread_rng().gen_range(NEW_TARGET_DISTANCE[0]..=NEW_TARGET_DISTANCE[1]);
-    let angle = rand::thread_rng().gen_range(0f32..std::f32::consts::TAU); // 0 to 360 but in radians
+    let distance: f32 = random::rng().gen_range(NEW_TARGET_DISTANCE[0]..=NEW_TARGET_DISTANCE[1]);
+    let angle = random::rng().gen_range(0f32..std::f32::consts::TAU); // 0 to 360 but in radians
 
     let direction_vector = vector_helper::create_direction_vector(angle);
     let target_relative = vecmath::vec2_scale(direction_vector, distance);
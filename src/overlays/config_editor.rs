@@ -0,0 +1,662 @@
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, Mesh, Rect, Text},
+    winit::event::VirtualKeyCode,
+};
+
+use crate::{
+    configurations::{
+        generation_configuration::GenerationConfiguration,
+        species_generation_configuration::NUMERIC_PARAMETERS,
+    },
+    organisms::species::{
+        Activity, HuntingBehavior, Nutrition, OrganismShape, SizeScaling, Species,
+    },
+};
+
+const LEFT: f32 = 10.0;
+const TOP: f32 = 10.0;
+const WIDTH: f32 = 360.0;
+const ROW_HEIGHT: f32 = 18.0;
+const PADDING: f32 = 6.0;
+/// Where values start, relative to the left of the panel.
+const VALUE_COLUMN: f32 = 190.0;
+const BACKGROUND_COLOR: Color = Color {
+    r: 0.08,
+    g: 0.08,
+    b: 0.08,
+    a: 0.95,
+};
+const SELECTED_COLOR: Color = Color {
+    r: 0.25,
+    g: 0.25,
+    b: 0.35,
+    a: 1.0,
+};
+const ERROR_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.4,
+    b: 0.4,
+    a: 1.0,
+};
+
+/// A field of [`Species`] picked from a fixed list of options.
+#[derive(Clone, Copy, PartialEq)]
+enum ChoiceField {
+    Eats,
+    ContainedNutrition,
+    HuntingBehavior,
+    Shape,
+    SizeScaling,
+    Activity,
+}
+
+impl ChoiceField {
+    const ALL: [ChoiceField; 6] = [
+        ChoiceField::Eats,
+        ChoiceField::ContainedNutrition,
+        ChoiceField::HuntingBehavior,
+        ChoiceField::Shape,
+        ChoiceField::SizeScaling,
+        ChoiceField::Activity,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ChoiceField::Eats => "eats",
+            ChoiceField::ContainedNutrition => "contained_nutrition",
+            ChoiceField::HuntingBehavior => "hunting_behavior",
+            ChoiceField::Shape => "shape",
+            ChoiceField::SizeScaling => "size_scaling",
+            ChoiceField::Activity => "activity",
+        }
+    }
+
+    fn options(self) -> Vec<&'static str> {
+        match self {
+            ChoiceField::Eats | ChoiceField::ContainedNutrition => {
+                Nutrition::ALL.iter().map(|option| option.name()).collect()
+            }
+            ChoiceField::HuntingBehavior => HuntingBehavior::ALL
+                .iter()
+                .map(|option| option.name())
+                .collect(),
+            ChoiceField::Shape => OrganismShape::ALL
+                .iter()
+                .map(|option| option.name())
+                .collect(),
+            ChoiceField::SizeScaling => SizeScaling::ALL
+                .iter()
+                .map(|option| option.name())
+                .collect(),
+            ChoiceField::Activity => Activity::ALL.iter().map(|option| option.name()).collect(),
+        }
+    }
+
+    fn selected(self, species: &Species) -> usize {
+        let position = match self {
+            ChoiceField::Eats => Nutrition::ALL.iter().position(|x| *x == species.eats),
+            ChoiceField::ContainedNutrition => Nutrition::ALL
+                .iter()
+                .position(|x| *x == species.contained_nutrition),
+            ChoiceField::HuntingBehavior => HuntingBehavior::ALL
+                .iter()
+                .position(|x| *x == species.hunting_behavior),
+            ChoiceField::Shape => OrganismShape::ALL.iter().position(|x| *x == species.shape),
+            ChoiceField::SizeScaling => SizeScaling::ALL
+                .iter()
+                .position(|x| *x == species.size_scaling),
+            ChoiceField::Activity => Activity::ALL.iter().position(|x| *x == species.activity),
+        };
+        position.unwrap_or(0)
+    }
+
+    fn select(self, species: &mut Species, index: usize) {
+        match self {
+            ChoiceField::Eats => species.eats = Nutrition::ALL[index],
+            ChoiceField::ContainedNutrition => {
+                species.contained_nutrition = Nutrition::ALL[index];
+            }
+            ChoiceField::HuntingBehavior => {
+                species.hunting_behavior = HuntingBehavior::ALL[index];
+            }
+            ChoiceField::Shape => species.shape = OrganismShape::ALL[index],
+            ChoiceField::SizeScaling => species.size_scaling = SizeScaling::ALL[index],
+            ChoiceField::Activity => species.activity = Activity::ALL[index],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EditorButton {
+    ApplyAndRestart,
+    SaveAndRestart,
+    Revert,
+}
+
+impl EditorButton {
+    const ALL: [EditorButton; 3] = [
+        EditorButton::ApplyAndRestart,
+        EditorButton::SaveAndRestart,
+        EditorButton::Revert,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            EditorButton::ApplyAndRestart => "[ apply and restart ]",
+            EditorButton::SaveAndRestart => "[ save to file and restart ]",
+            EditorButton::Revert => "[ revert changes ]",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    /// Switches between the species of the configuration.
+    Species,
+    Numeric(&'static str),
+    /// A red, green or blue slider of the species color.
+    ColorChannel(usize),
+    Choice(ChoiceField),
+    Button(EditorButton),
+}
+
+/// What the editor wants done with the edited configuration.
+pub enum ConfigEditorAction {
+    None,
+    ApplyAndRestart(GenerationConfiguration),
+    SaveAndRestart(GenerationConfiguration),
+}
+
+/// A panel for changing the species configuration while the viewer runs.
+/// Up and down pick a field, left and right change it,
+/// numbers can be typed in and enter confirms them or opens a dropdown.
+pub struct ConfigEditor {
+    visible: bool,
+    /// The configuration the editor was opened with.
+    original: Option<GenerationConfiguration>,
+    config: Option<GenerationConfiguration>,
+    species_index: usize,
+    selected_row: usize,
+    /// Text typed into the selected numeric field, not applied yet.
+    input: Option<String>,
+    dropdown_open: bool,
+    dragging_color_channel: Option<usize>,
+    status: Option<(String, bool)>,
+    /// Rows as they were drawn last, for hit testing.
+    row_rects: Vec<Rect>,
+    dropdown_rects: Vec<Rect>,
+}
+
+impl ConfigEditor {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            original: None,
+            config: None,
+            species_index: 0,
+            selected_row: 0,
+            input: None,
+            dropdown_open: false,
+            dragging_color_channel: None,
+            status: None,
+            row_rects: Vec::new(),
+            dropdown_rects: Vec::new(),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows the editor with a copy of `config`, or hides it, dropping unapplied changes.
+    pub fn toggle(&mut self, config: &GenerationConfiguration) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.original = Some(config.clone());
+            self.config = Some(config.clone());
+            self.species_index = self
+                .species_index
+                .min(config.species.len().saturating_sub(1));
+            self.input = None;
+            self.dropdown_open = false;
+            self.status = None;
+        }
+    }
+
+    /// Shows how applying or saving the configuration went.
+    pub fn set_status(&mut self, message: String, is_error: bool) {
+        self.status = Some((message, is_error));
+    }
+
+    fn rows() -> Vec<Row> {
+        let mut rows = vec![Row::Species];
+        rows.extend(NUMERIC_PARAMETERS.into_iter().map(Row::Numeric));
+        rows.extend((0..3).map(Row::ColorChannel));
+        rows.extend(ChoiceField::ALL.iter().map(|field| Row::Choice(*field)));
+        rows.extend(EditorButton::ALL.iter().map(|button| Row::Button(*button)));
+        rows
+    }
+
+    fn selected(&self) -> Row {
+        Self::rows()[self.selected_row]
+    }
+
+    fn select_row(&mut self, row: usize) {
+        if row != self.selected_row {
+            self.input = None;
+            self.dropdown_open = false;
+        }
+        self.selected_row = row;
+    }
+
+    fn species_mut(&mut self) -> Option<&mut Species> {
+        let species_index = self.species_index;
+        self.config
+            .as_mut()?
+            .species
+            .get_mut(species_index)
+            .map(|config| &mut config.species)
+    }
+
+    /// Returns what should happen after the key was pressed.
+    /// Every key is used while the editor is visible.
+    pub fn key_down_event(&mut self, keycode: VirtualKeyCode) -> ConfigEditorAction {
+        let row_count = Self::rows().len();
+        match keycode {
+            VirtualKeyCode::Escape if self.input.is_some() => self.input = None,
+            VirtualKeyCode::Escape if self.dropdown_open => self.dropdown_open = false,
+            VirtualKeyCode::Escape => self.visible = false,
+            VirtualKeyCode::Up if self.dropdown_open => self.change_selected(-1),
+            VirtualKeyCode::Down if self.dropdown_open => self.change_selected(1),
+            VirtualKeyCode::Up => {
+                self.select_row((self.selected_row + row_count - 1) % row_count);
+            }
+            VirtualKeyCode::Down => self.select_row((self.selected_row + 1) % row_count),
+            VirtualKeyCode::Left => self.change_selected(-1),
+            VirtualKeyCode::Right => self.change_selected(1),
+            VirtualKeyCode::Back => {
+                if let Some(input) = self.input.as_mut() {
+                    input.pop();
+                }
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => return self.confirm(),
+            _ => {}
+        }
+        ConfigEditorAction::None
+    }
+
+    /// Typed characters go into the selected numeric field.
+    pub fn text_input_event(&mut self, character: char) {
+        if !matches!(self.selected(), Row::Numeric(_))
+            || !(character.is_ascii_digit() || character == '.')
+        {
+            return;
+        }
+        self.input.get_or_insert_with(String::new).push(character);
+    }
+
+    fn confirm(&mut self) -> ConfigEditorAction {
+        match self.selected() {
+            Row::Numeric(name) => {
+                if let Some(input) = self.input.take() {
+                    self.set_numeric(name, &input);
+                }
+            }
+            Row::Choice(_) => self.dropdown_open = !self.dropdown_open,
+            Row::Button(button) => return self.press(button),
+            Row::Species | Row::ColorChannel(_) => {}
+        }
+        ConfigEditorAction::None
+    }
+
+    fn set_numeric(&mut self, name: &str, input: &str) {
+        let species_index = self.species_index;
+        let Some(config) = self
+            .config
+            .as_mut()
+            .and_then(|config| config.species.get_mut(species_index))
+        else {
+            return;
+        };
+        let result = input
+            .parse::<f32>()
+            .map_err(|_| format!("{} is not a number", input))
+            .and_then(|value| config.set_parameter(name, value));
+        match result {
+            Ok(()) => self.status = None,
+            Err(message) => self.set_status(message, true),
+        }
+    }
+
+    /// Moves the selected field by one step in `direction`.
+    fn change_selected(&mut self, direction: i32) {
+        match self.selected() {
+            Row::Species => {
+                let count = self.config.as_ref().map_or(0, |c| c.species.len());
+                if count > 0 {
+                    self.species_index =
+                        (self.species_index as i32 + direction).rem_euclid(count as i32) as usize;
+                }
+            }
+            Row::Numeric(name) => {
+                self.input = None;
+                let species_index = self.species_index;
+                let Some(config) = self
+                    .config
+                    .as_mut()
+                    .and_then(|config| config.species.get_mut(species_index))
+                else {
+                    return;
+                };
+                let value = config.parameter(name).unwrap_or(0.0);
+                let step = (value.abs() * 0.1).max(0.1);
+                let _ = config.set_parameter(name, (value + step * direction as f32).max(0.0));
+            }
+            Row::ColorChannel(channel) => {
+                if let Some(species) = self.species_mut() {
+                    let value =
+                        color_channel(&species.color, channel) + 5.0 / 255.0 * direction as f32;
+                    set_color_channel(&mut species.color, channel, value);
+                }
+            }
+            Row::Choice(field) => {
+                let count = field.options().len() as i32;
+                if let Some(species) = self.species_mut() {
+                    let index = (field.selected(species) as i32 + direction).rem_euclid(count);
+                    field.select(species, index as usize);
+                }
+            }
+            Row::Button(_) => {}
+        }
+    }
+
+    fn press(&mut self, button: EditorButton) -> ConfigEditorAction {
+        let Some(config) = self.config.as_ref() else {
+            return ConfigEditorAction::None;
+        };
+        match button {
+            EditorButton::ApplyAndRestart => ConfigEditorAction::ApplyAndRestart(config.clone()),
+            EditorButton::SaveAndRestart => ConfigEditorAction::SaveAndRestart(config.clone()),
+            EditorButton::Revert => {
+                self.config = self.original.clone();
+                self.input = None;
+                self.status = None;
+                ConfigEditorAction::None
+            }
+        }
+    }
+
+    /// Returns whether the click landed on the editor and what should happen after it.
+    pub fn mouse_button_down_event(&mut self, x: f32, y: f32) -> (bool, ConfigEditorAction) {
+        if !self.visible {
+            return (false, ConfigEditorAction::None);
+        }
+
+        if self.dropdown_open {
+            if let Some(index) = self.dropdown_rects.iter().position(|r| r.contains([x, y])) {
+                if let (Row::Choice(field), Some(species)) = (self.selected(), self.species_mut()) {
+                    field.select(species, index);
+                }
+                self.dropdown_open = false;
+                return (true, ConfigEditorAction::None);
+            }
+            self.dropdown_open = false;
+        }
+
+        let Some(row) = self.row_rects.iter().position(|r| r.contains([x, y])) else {
+            let on_panel = self.panel_rect().contains([x, y]);
+            return (on_panel, ConfigEditorAction::None);
+        };
+        self.select_row(row);
+        let row_rect = self.row_rects[row];
+
+        let action = match self.selected() {
+            Row::Species => {
+                // the left half goes back, the right half forward
+                let direction = if x < row_rect.center().x { -1 } else { 1 };
+                self.change_selected(direction);
+                ConfigEditorAction::None
+            }
+            Row::ColorChannel(channel) => {
+                self.dragging_color_channel = Some(channel);
+                self.drag_color_channel(x);
+                ConfigEditorAction::None
+            }
+            Row::Choice(_) => {
+                self.dropdown_open = true;
+                ConfigEditorAction::None
+            }
+            Row::Button(button) => self.press(button),
+            Row::Numeric(_) => ConfigEditorAction::None,
+        };
+        (true, action)
+    }
+
+    pub fn mouse_button_up_event(&mut self) {
+        self.dragging_color_channel = None;
+    }
+
+    /// Returns whether the motion was used by the editor.
+    pub fn mouse_motion_event(&mut self, x: f32) -> bool {
+        if self.dragging_color_channel.is_none() {
+            return false;
+        }
+        self.drag_color_channel(x);
+        true
+    }
+
+    fn drag_color_channel(&mut self, x: f32) {
+        let Some(channel) = self.dragging_color_channel else {
+            return;
+        };
+        let slider = slider_rect(&self.panel_rect(), 0.0);
+        let value = (x - slider.x) / slider.w;
+        if let Some(species) = self.species_mut() {
+            set_color_channel(&mut species.color, channel, value);
+        }
+    }
+
+    fn panel_rect(&self) -> Rect {
+        let rows = Self::rows().len() as f32 + 1.0;
+        Rect::new(LEFT, TOP, WIDTH, rows * ROW_HEIGHT + PADDING * 2.0)
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas, gfx: &impl Has<GraphicsContext>) {
+        self.dropdown_rects.clear();
+        let mut row_rects = std::mem::take(&mut self.row_rects);
+        row_rects.clear();
+        self.draw_rows(canvas, gfx, &mut row_rects);
+        self.row_rects = row_rects;
+        self.draw_dropdown(canvas, gfx);
+    }
+
+    fn draw_rows(
+        &self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        row_rects: &mut Vec<Rect>,
+    ) {
+        if !self.visible {
+            return;
+        }
+        let Some(config) = self.config.as_ref() else {
+            return;
+        };
+
+        let panel_rect = self.panel_rect();
+        draw_rectangle(canvas, gfx, panel_rect, BACKGROUND_COLOR);
+
+        let species_config = config.species.get(self.species_index);
+        let rows = Self::rows();
+        for (i, row) in rows.iter().enumerate() {
+            let row_rect = Rect::new(
+                panel_rect.x,
+                panel_rect.y + PADDING + i as f32 * ROW_HEIGHT,
+                panel_rect.w,
+                ROW_HEIGHT,
+            );
+            row_rects.push(row_rect);
+            if i == self.selected_row {
+                draw_rectangle(canvas, gfx, row_rect, SELECTED_COLOR);
+            }
+
+            let (label, value) = match (row, species_config) {
+                (Row::Species, Some(species_config)) => (
+                    String::from("species"),
+                    format!(
+                        "< {} ({}/{}) >",
+                        species_config.species.name,
+                        self.species_index + 1,
+                        config.species.len()
+                    ),
+                ),
+                (Row::Species, None) => (String::from("species"), String::from("none")),
+                (Row::Numeric(name), Some(species_config)) => {
+                    let value = match (&self.input, i == self.selected_row) {
+                        (Some(input), true) => format!("{}_", input),
+                        _ => format!("{:.2}", species_config.parameter(name).unwrap_or(0.0)),
+                    };
+                    (name.to_string(), value)
+                }
+                (Row::ColorChannel(channel), Some(species_config)) => {
+                    let color = species_config.species.color;
+                    let slider = slider_rect(&panel_rect, row_rect.y);
+                    draw_rectangle(canvas, gfx, slider, Color::new(0.3, 0.3, 0.3, 1.0));
+                    let mut fill = Color::new(0.0, 0.0, 0.0, 1.0);
+                    set_color_channel(&mut fill, *channel, 1.0);
+                    let fraction = color_channel(&color, *channel);
+                    draw_rectangle(
+                        canvas,
+                        gfx,
+                        Rect::new(slider.x, slider.y, slider.w * fraction, slider.h),
+                        fill,
+                    );
+                    if *channel == 0 {
+                        // a preview of the whole color next to the sliders
+                        draw_rectangle(
+                            canvas,
+                            gfx,
+                            Rect::new(
+                                panel_rect.x + VALUE_COLUMN - ROW_HEIGHT * 3.0 - PADDING,
+                                row_rect.y,
+                                ROW_HEIGHT * 3.0,
+                                ROW_HEIGHT * 3.0,
+                            ),
+                            color,
+                        );
+                    }
+                    let label = ["color red", "color green", "color blue"][*channel];
+                    (label.to_string(), String::new())
+                }
+                (Row::Choice(field), Some(species_config)) => {
+                    let options = field.options();
+                    let value = options[field.selected(&species_config.species)];
+                    (field.label().to_string(), format!("{} v", value))
+                }
+                (Row::Button(button), _) => (button.label().to_string(), String::new()),
+                (_, None) => continue,
+            };
+
+            canvas.draw(
+                &Text::new(label),
+                DrawParam::default().dest([row_rect.x + PADDING, row_rect.y + 1.0]),
+            );
+            if !value.is_empty() {
+                canvas.draw(
+                    &Text::new(value),
+                    DrawParam::default().dest([row_rect.x + VALUE_COLUMN, row_rect.y + 1.0]),
+                );
+            }
+        }
+
+        let status_y = panel_rect.bottom() - PADDING - ROW_HEIGHT;
+        let (status, color) = match &self.status {
+            Some((message, true)) => (message.clone(), ERROR_COLOR),
+            Some((message, false)) => (message.clone(), Color::WHITE),
+            None => (
+                String::from("E or Escape closes the editor"),
+                Color::new(0.6, 0.6, 0.6, 1.0),
+            ),
+        };
+        canvas.draw(
+            &Text::new(status),
+            DrawParam::default()
+                .dest([panel_rect.x + PADDING, status_y + 1.0])
+                .color(color),
+        );
+    }
+
+    fn draw_dropdown(&mut self, canvas: &mut Canvas, gfx: &impl Has<GraphicsContext>) {
+        let Row::Choice(field) = self.selected() else {
+            return;
+        };
+        if !self.visible || !self.dropdown_open {
+            return;
+        }
+        let Some(row_rect) = self.row_rects.get(self.selected_row).copied() else {
+            return;
+        };
+        let selected = self
+            .config
+            .as_ref()
+            .and_then(|config| config.species.get(self.species_index))
+            .map_or(0, |species_config| field.selected(&species_config.species));
+
+        for (i, option) in field.options().into_iter().enumerate() {
+            let option_rect = Rect::new(
+                row_rect.x + VALUE_COLUMN - PADDING,
+                row_rect.bottom() + i as f32 * ROW_HEIGHT,
+                WIDTH - VALUE_COLUMN,
+                ROW_HEIGHT,
+            );
+            let background = if i == selected {
+                SELECTED_COLOR
+            } else {
+                Color::new(0.15, 0.15, 0.15, 1.0)
+            };
+            draw_rectangle(canvas, gfx, option_rect, background);
+            canvas.draw(
+                &Text::new(option),
+                DrawParam::default().dest([option_rect.x + PADDING, option_rect.y + 1.0]),
+            );
+            self.dropdown_rects.push(option_rect);
+        }
+    }
+}
+
+/// The bar of a color slider in the row starting at `row_y`.
+fn slider_rect(panel_rect: &Rect, row_y: f32) -> Rect {
+    Rect::new(
+        panel_rect.x + VALUE_COLUMN,
+        row_y + 4.0,
+        WIDTH - VALUE_COLUMN - PADDING * 2.0,
+        ROW_HEIGHT - 8.0,
+    )
+}
+
+fn color_channel(color: &Color, channel: usize) -> f32 {
+    match channel {
+        0 => color.r,
+        1 => color.g,
+        _ => color.b,
+    }
+}
+
+fn set_color_channel(color: &mut Color, channel: usize, value: f32) {
+    let value = value.clamp(0.0, 1.0);
+    match channel {
+        0 => color.r = value,
+        1 => color.g = value,
+        _ => color.b = value,
+    }
+}
+
+fn draw_rectangle(canvas: &mut Canvas, gfx: &impl Has<GraphicsContext>, rect: Rect, color: Color) {
+    if rect.w <= 0.0 || rect.h <= 0.0 {
+        return;
+    }
+    if let Ok(mesh) = Mesh::new_rectangle(gfx, DrawMode::fill(), rect, color) {
+        canvas.draw(&mesh, DrawParam::default());
+    }
+}
@@ -0,0 +1,228 @@
+use ggez::{
+    context::Has,
+    graphics::{
+        Canvas, Color, DrawMode, DrawParam, GraphicsContext, Mesh, MeshBuilder, Rect, Text,
+    },
+    mint::Point2,
+};
+
+use crate::{organisms::organism_info::OrganismInfo, simulation_thread::SimulationData};
+
+const EYESIGHT_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 0.3,
+    a: 0.25,
+};
+const HUNTING_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.2,
+    b: 0.2,
+    a: 0.7,
+};
+const EATING_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.5,
+    b: 0.0,
+    a: 0.6,
+};
+const CHUNK_COLOR: Color = Color {
+    r: 0.0,
+    g: 0.8,
+    b: 0.8,
+    a: 0.5,
+};
+const LINE_WIDTH: f32 = 1.0;
+/// Circles smaller than this many pixels aren't worth drawing.
+const MIN_RADIUS_PX: f32 = 1.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DebugLayer {
+    Eyesight,
+    Hunting,
+    EatingDistance,
+    Chunks,
+}
+
+impl DebugLayer {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DebugLayer::Eyesight => "eyesight",
+            DebugLayer::Hunting => "hunting",
+            DebugLayer::EatingDistance => "eating distance",
+            DebugLayer::Chunks => "chunks",
+        }
+    }
+}
+
+/// Togglable layers drawn on top of the organisms to show why they behave like they do.
+/// Everything is taken from the last snapshot of the simulation.
+pub struct DebugLayers {
+    enabled: Vec<DebugLayer>,
+}
+
+impl DebugLayers {
+    pub fn new() -> Self {
+        Self {
+            enabled: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self, layer: DebugLayer) -> bool {
+        self.enabled.contains(&layer)
+    }
+
+    pub fn toggle(&mut self, layer: DebugLayer) {
+        if self.is_enabled(layer) {
+            self.enabled.retain(|enabled| *enabled != layer);
+        } else {
+            self.enabled.push(layer);
+        }
+    }
+
+    /// The names of the enabled layers, for the HUD.
+    pub fn description(&self) -> Option<String> {
+        if self.enabled.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = self.enabled.iter().map(|layer| layer.name()).collect();
+        Some(names.join(", "))
+    }
+
+    /// `world_to_screen` converts world coordinates into screen coordinates
+    /// and `is_visible` tells which organisms are shown at all.
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        data: &SimulationData,
+        zoom: f32,
+        world_to_screen: impl Fn(Point2<f32>) -> Point2<f32>,
+        is_visible: impl Fn(&OrganismInfo) -> bool,
+    ) {
+        if self.enabled.is_empty() {
+            return;
+        }
+        let display_screen_rect = &canvas.screen_coordinates().unwrap();
+
+        let mut builder = MeshBuilder::new();
+        let mut shapes = 0;
+
+        if self.is_enabled(DebugLayer::Chunks) {
+            for (chunk_rect, _) in &data.chunk_occupancy {
+                let screen_rect = world_rect_to_screen(chunk_rect, &world_to_screen);
+                if screen_rect.overlaps(display_screen_rect)
+                    && builder
+                        .rectangle(DrawMode::stroke(LINE_WIDTH), screen_rect, CHUNK_COLOR)
+                        .is_ok()
+                {
+                    shapes += 1;
+                }
+            }
+        }
+
+        for info in data.organism_infos.iter().filter(|info| is_visible(info)) {
+            let species = &info.shared_state().species;
+            let screen_position = world_to_screen(info.shared_state().position);
+
+            let mut circles = Vec::with_capacity(2);
+            if self.is_enabled(DebugLayer::Eyesight) {
+                circles.push((species.eyesight_distance * zoom, EYESIGHT_COLOR));
+            }
+            if self.is_enabled(DebugLayer::EatingDistance) {
+                circles.push((species.eating_distance * zoom, EATING_COLOR));
+            }
+            for (radius, color) in circles {
+                if radius < MIN_RADIUS_PX
+                    || !circle_bounds(screen_position, radius).overlaps(display_screen_rect)
+                {
+                    continue;
+                }
+                let added = builder.circle(
+                    DrawMode::stroke(LINE_WIDTH),
+                    screen_position,
+                    radius,
+                    (radius * 0.005).max(0.1),
+                    color,
+                );
+                if added.is_ok() {
+                    shapes += 1;
+                }
+            }
+
+            if !self.is_enabled(DebugLayer::Hunting) {
+                continue;
+            }
+            if let Some(target) = info.hunting_target() {
+                let screen_target = world_to_screen(target);
+                if screen_target != screen_position
+                    && builder
+                        .line(&[screen_position, screen_target], LINE_WIDTH, HUNTING_COLOR)
+                        .is_ok()
+                {
+                    shapes += 1;
+                }
+            }
+        }
+
+        // an empty mesh can't be created
+        if shapes > 0 {
+            let mesh = Mesh::from_data(gfx, builder.build());
+            canvas.draw(&mesh, DrawParam::default());
+        }
+
+        if self.is_enabled(DebugLayer::Chunks) {
+            self.draw_chunk_counts(canvas, data, display_screen_rect, &world_to_screen);
+        }
+    }
+
+    fn draw_chunk_counts(
+        &self,
+        canvas: &mut Canvas,
+        data: &SimulationData,
+        display_screen_rect: &Rect,
+        world_to_screen: &impl Fn(Point2<f32>) -> Point2<f32>,
+    ) {
+        for (chunk_rect, count) in &data.chunk_occupancy {
+            if *count == 0 {
+                continue;
+            }
+            let screen_rect = world_rect_to_screen(chunk_rect, world_to_screen);
+            if !screen_rect.overlaps(display_screen_rect) {
+                continue;
+            }
+            canvas.draw(
+                &Text::new(count.to_string()),
+                DrawParam::default()
+                    .dest([screen_rect.x + 3.0, screen_rect.y + 2.0])
+                    .color(CHUNK_COLOR),
+            );
+        }
+    }
+}
+
+fn world_rect_to_screen(
+    world_rect: &Rect,
+    world_to_screen: &impl Fn(Point2<f32>) -> Point2<f32>,
+) -> Rect {
+    let top_left = world_to_screen(world_rect.point());
+    let bottom_right = world_to_screen(Point2 {
+        x: world_rect.right(),
+        y: world_rect.bottom(),
+    });
+    Rect::new(
+        top_left.x,
+        top_left.y,
+        bottom_right.x - top_left.x,
+        bottom_right.y - top_left.y,
+    )
+}
+
+fn circle_bounds(center: Point2<f32>, radius: f32) -> Rect {
+    Rect::new(
+        center.x - radius,
+        center.y - radius,
+        radius * 2.0,
+        radius * 2.0,
+    )
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use ggez::{
+    context::Has,
+    graphics::{
+        BlendMode, Canvas, Color, DrawMode, DrawParam, GraphicsContext, InstanceArray, Mesh, Rect,
+    },
+    mint::Point2,
+};
+
+use crate::{simulation::BOUNDARY_DISTANCE_FROM_CENTER, simulation_thread::SimulationData};
+
+/// Width and height of a heatmap cell in world units.
+/// Cell (0, 0) starts at the center of the world.
+const CELL_SIZE: f32 = 5.0;
+/// Alpha of the cells with the lowest and the highest value,
+/// so that the organisms and terrain below stay visible.
+const MIN_ALPHA: f32 = 0.35;
+const MAX_ALPHA: f32 = 0.6;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapMode {
+    Off,
+    /// How many organisms are in a cell.
+    AllOrganisms,
+    /// How many organisms of the chosen species are in a cell.
+    Species,
+    /// The summed energy of the organisms in a cell.
+    Energy,
+    /// The nutrients in the soil, per square meter.
+    Nutrients,
+}
+
+impl HeatmapMode {
+    pub fn next(&self) -> Self {
+        match self {
+            HeatmapMode::Off => HeatmapMode::AllOrganisms,
+            HeatmapMode::AllOrganisms => HeatmapMode::Species,
+            HeatmapMode::Species => HeatmapMode::Energy,
+            HeatmapMode::Energy => HeatmapMode::Nutrients,
+            HeatmapMode::Nutrients => HeatmapMode::Off,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorRamp {
+    Heat,
+    Viridis,
+    Grayscale,
+}
+
+impl ColorRamp {
+    pub fn next(&self) -> Self {
+        match self {
+            ColorRamp::Heat => ColorRamp::Viridis,
+            ColorRamp::Viridis => ColorRamp::Grayscale,
+            ColorRamp::Grayscale => ColorRamp::Heat,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorRamp::Heat => "heat",
+            ColorRamp::Viridis => "viridis",
+            ColorRamp::Grayscale => "grayscale",
+        }
+    }
+
+    /// Evenly spaced colors, from the lowest value to the highest.
+    fn stops(&self) -> &'static [[f32; 3]] {
+        match self {
+            ColorRamp::Heat => &[
+                [0.1, 0.0, 0.0],
+                [0.8, 0.0, 0.0],
+                [1.0, 0.8, 0.0],
+                [1.0, 1.0, 1.0],
+            ],
+            ColorRamp::Viridis => &[
+                [0.27, 0.0, 0.33],
+                [0.23, 0.32, 0.55],
+                [0.13, 0.57, 0.55],
+                [0.37, 0.79, 0.38],
+                [0.99, 0.91, 0.14],
+            ],
+            ColorRamp::Grayscale => &[[0.08, 0.08, 0.08], [1.0, 1.0, 1.0]],
+        }
+    }
+
+    /// The color for `fraction` between 0 and 1, more opaque for higher values.
+    pub fn color(&self, fraction: f32) -> Color {
+        let stops = self.stops();
+        let fraction = fraction.clamp(0.0, 1.0);
+        let position = fraction * (stops.len() - 1) as f32;
+        let index = (position.floor() as usize).min(stops.len() - 2);
+        let t = position - index as f32;
+        let [r0, g0, b0] = stops[index];
+        let [r1, g1, b1] = stops[index + 1];
+        Color::new(
+            r0 + (r1 - r0) * t,
+            g0 + (g1 - g0) * t,
+            b0 + (b1 - b0) * t,
+            MIN_ALPHA + (MAX_ALPHA - MIN_ALPHA) * fraction,
+        )
+    }
+}
+
+/// Shows how organisms, their energy or the nutrients in the soil are spread over the world,
+/// which stays readable when there are too many organisms to tell apart.
+pub struct Heatmap {
+    mode: HeatmapMode,
+    species: Option<String>,
+    ramp: ColorRamp,
+    cells: HashMap<Point2<i32>, f32>,
+    max_value: f32,
+    /// The step of the snapshot the cells were computed from.
+    computed_step: Option<u64>,
+    cell_mesh: Option<Mesh>,
+    instances: Option<InstanceArray>,
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Self {
+            mode: HeatmapMode::Off,
+            species: None,
+            ramp: ColorRamp::Heat,
+            cells: HashMap::new(),
+            max_value: 0.0,
+            computed_step: None,
+            cell_mesh: None,
+            instances: None,
+        }
+    }
+
+    pub fn next_mode(&mut self) {
+        self.mode = self.mode.next();
+        self.computed_step = None;
+    }
+
+    pub fn next_ramp(&mut self) {
+        self.ramp = self.ramp.next();
+    }
+
+    /// Switches the species shown in [`HeatmapMode::Species`] to the next one by name.
+    pub fn next_species(&mut self, data: &SimulationData) {
+        let mut names: Vec<&String> = data.organism_counter.keys().collect();
+        names.sort();
+        let next = match &self.species {
+            Some(current) => names
+                .iter()
+                .position(|name| *name == current)
+                .and_then(|i| names.get(i + 1))
+                .or(names.first()),
+            None => names.first(),
+        };
+        self.species = next.map(|name| (*name).clone());
+        self.computed_step = None;
+    }
+
+    /// What the heatmap shows, for the HUD.
+    pub fn description(&self) -> Option<String> {
+        let shown = match self.mode {
+            HeatmapMode::Off => return None,
+            HeatmapMode::AllOrganisms => String::from("all organisms"),
+            HeatmapMode::Species => match &self.species {
+                Some(species) => species.clone(),
+                None => String::from("no species"),
+            },
+            HeatmapMode::Energy => String::from("energy"),
+            HeatmapMode::Nutrients => String::from("nutrients"),
+        };
+        Some(format!("{} ({})", shown, self.ramp.name()))
+    }
+
+    fn compute(&mut self, data: &SimulationData) {
+        if self.computed_step == Some(data.step) {
+            return;
+        }
+        self.computed_step = Some(data.step);
+
+        if self.mode == HeatmapMode::Species && self.species.is_none() {
+            self.next_species(data);
+        }
+
+        self.cells.clear();
+        if self.mode == HeatmapMode::Nutrients {
+            self.compute_nutrients(data);
+            return;
+        }
+        for info in data.organism_infos.iter() {
+            let shared_state = info.shared_state();
+            let value = match self.mode {
+                HeatmapMode::Off | HeatmapMode::Nutrients => return,
+                HeatmapMode::AllOrganisms => 1.0,
+                HeatmapMode::Species
+                    if self.species.as_ref() == Some(&shared_state.species.name) =>
+                {
+                    1.0
+                }
+                HeatmapMode::Species => continue,
+                HeatmapMode::Energy => shared_state.energy(),
+            };
+            let index = Point2 {
+                x: (shared_state.position.x / CELL_SIZE).floor() as i32,
+                y: (shared_state.position.y / CELL_SIZE).floor() as i32,
+            };
+            *self.cells.entry(index).or_default() += value;
+        }
+        self.max_value = self.cells.values().copied().fold(0.0, f32::max);
+    }
+
+    /// Samples the nutrient field in the middle of every cell of the world.
+    fn compute_nutrients(&mut self, data: &SimulationData) {
+        if data.nutrients.is_enabled() {
+            let cells = (BOUNDARY_DISTANCE_FROM_CENTER / CELL_SIZE).ceil() as i32;
+            for y in -cells..cells {
+                for x in -cells..cells {
+                    let center = Point2 {
+                        x: (x as f32 + 0.5) * CELL_SIZE,
+                        y: (y as f32 + 0.5) * CELL_SIZE,
+                    };
+                    self.cells
+                        .insert(Point2 { x, y }, data.nutrients.density_at(center));
+                }
+            }
+        }
+        self.max_value = self.cells.values().copied().fold(0.0, f32::max);
+    }
+
+    /// Draws the cells, meant to go under the organisms.
+    /// `world_to_screen` converts world coordinates into screen coordinates.
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        data: &SimulationData,
+        world_to_screen: impl Fn(Point2<f32>) -> Point2<f32>,
+    ) {
+        if self.mode == HeatmapMode::Off {
+            return;
+        }
+        self.compute(data);
+        if self.max_value <= 0.0 {
+            return;
+        }
+
+        let display_screen_rect = canvas.screen_coordinates().unwrap();
+        let cell_mesh = self.cell_mesh.get_or_insert_with(|| {
+            Mesh::new_rectangle(
+                gfx,
+                DrawMode::fill(),
+                Rect::new(0.0, 0.0, 1.0, 1.0),
+                Color::WHITE,
+            )
+            .unwrap()
+        });
+        let instances = self
+            .instances
+            .get_or_insert_with(|| InstanceArray::new(gfx, None));
+        instances.clear();
+
+        for (index, value) in self.cells.iter() {
+            let top_left = world_to_screen(Point2 {
+                x: index.x as f32 * CELL_SIZE,
+                y: index.y as f32 * CELL_SIZE,
+            });
+            let bottom_right = world_to_screen(Point2 {
+                x: (index.x + 1) as f32 * CELL_SIZE,
+                y: (index.y + 1) as f32 * CELL_SIZE,
+            });
+            let screen_rect = Rect::new(
+                top_left.x,
+                top_left.y,
+                bottom_right.x - top_left.x,
+                bottom_right.y - top_left.y,
+            );
+            if !screen_rect.overlaps(&display_screen_rect) {
+                continue;
+            }
+            instances.push(
+                DrawParam::default()
+                    .dest(top_left)
+                    .scale([screen_rect.w, screen_rect.h])
+                    .color(self.ramp.color(value / self.max_value)),
+            );
+        }
+
+        // the frame is drawn replacing colors, which would ignore the alpha
+        let blend_mode = canvas.blend_mode();
+        canvas.set_blend_mode(BlendMode::ALPHA);
+        canvas.draw_instanced_mesh(cell_mesh.clone(), instances, DrawParam::default());
+        canvas.set_blend_mode(blend_mode);
+    }
+}
@@ -0,0 +1,210 @@
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, InstanceArray, Mesh, Rect},
+    mint::Point2,
+};
+
+use crate::{
+    overlays::species_legend::SpeciesLegend, simulation::BOUNDARY_DISTANCE_FROM_CENTER,
+    simulation_thread::SimulationData,
+};
+
+const SIZE: f32 = 180.0;
+const MARGIN: f32 = 10.0;
+/// How many cells the world is split into along each side.
+const RESOLUTION: usize = 60;
+const BACKGROUND_COLOR: Color = Color {
+    r: 0.05,
+    g: 0.05,
+    b: 0.05,
+    a: 0.85,
+};
+const VIEWPORT_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 0.9,
+};
+
+/// The whole world in a corner of the screen, with the part the camera sees outlined.
+/// Each pixel block shows the average color of the organisms in that part of the world.
+pub struct Minimap {
+    visible: bool,
+    /// Where the minimap was drawn last, for hit testing.
+    screen_rect: Rect,
+    /// Average color of every cell, row by row.
+    cells: Vec<Option<Color>>,
+    /// The step of the snapshot and the visibility generation the cells were computed from.
+    computed: Option<(u64, u64)>,
+    cell_mesh: Option<Mesh>,
+    instances: Option<InstanceArray>,
+}
+
+impl Minimap {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            screen_rect: Rect::default(),
+            cells: vec![None; RESOLUTION * RESOLUTION],
+            computed: None,
+            cell_mesh: None,
+            instances: None,
+        }
+    }
+
+    pub fn toggle_visible(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// The world point under a point on the screen, if the minimap is there.
+    pub fn world_point_at(&self, x: f32, y: f32) -> Option<Point2<f32>> {
+        if !self.visible || !self.screen_rect.contains([x, y]) {
+            return None;
+        }
+        let world_size = 2.0 * BOUNDARY_DISTANCE_FROM_CENTER;
+        Some(Point2 {
+            x: (x - self.screen_rect.x) / self.screen_rect.w * world_size
+                - BOUNDARY_DISTANCE_FROM_CENTER,
+            y: (y - self.screen_rect.y) / self.screen_rect.h * world_size
+                - BOUNDARY_DISTANCE_FROM_CENTER,
+        })
+    }
+
+    /// Only organisms of species visible in `species_legend` are shown.
+    fn compute(&mut self, data: &SimulationData, species_legend: &SpeciesLegend) {
+        let computed = Some((data.step, species_legend.visibility_generation()));
+        if self.computed == computed {
+            return;
+        }
+        self.computed = computed;
+
+        let mut sums = vec![([0.0f32; 3], 0u32); RESOLUTION * RESOLUTION];
+        for info in data
+            .organism_infos
+            .iter()
+            .filter(|info| species_legend.is_visible(&info.shared_state().species.name))
+        {
+            let Some(index) = cell_index(info.shared_state().position) else {
+                continue;
+            };
+            let color = info.shared_state().species.color;
+            let (sum, count) = &mut sums[index];
+            sum[0] += color.r;
+            sum[1] += color.g;
+            sum[2] += color.b;
+            *count += 1;
+        }
+
+        for (cell, (sum, count)) in self.cells.iter_mut().zip(sums) {
+            *cell = if count == 0 {
+                None
+            } else {
+                let count = count as f32;
+                Some(Color::new(
+                    sum[0] / count,
+                    sum[1] / count,
+                    sum[2] / count,
+                    1.0,
+                ))
+            };
+        }
+    }
+
+    /// Draws the minimap with its bottom right corner at `corner`.
+    /// `viewport` is the part of the world visible on the screen.
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        data: &SimulationData,
+        corner: Point2<f32>,
+        viewport: Rect,
+        species_legend: &SpeciesLegend,
+    ) {
+        if !self.visible {
+            return;
+        }
+        self.compute(data, species_legend);
+
+        self.screen_rect = Rect::new(
+            corner.x - SIZE - MARGIN,
+            corner.y - SIZE - MARGIN,
+            SIZE,
+            SIZE,
+        );
+        let rect = self.screen_rect;
+
+        if let Ok(background) = Mesh::new_rectangle(gfx, DrawMode::fill(), rect, BACKGROUND_COLOR) {
+            canvas.draw(&background, DrawParam::default());
+        }
+
+        let cell_mesh = self.cell_mesh.get_or_insert_with(|| {
+            Mesh::new_rectangle(
+                gfx,
+                DrawMode::fill(),
+                Rect::new(0.0, 0.0, 1.0, 1.0),
+                Color::WHITE,
+            )
+            .unwrap()
+        });
+        let instances = self
+            .instances
+            .get_or_insert_with(|| InstanceArray::new(gfx, None));
+        instances.clear();
+
+        let cell_size = SIZE / RESOLUTION as f32;
+        for (index, cell) in self.cells.iter().enumerate() {
+            let Some(color) = cell else {
+                continue;
+            };
+            let x = (index % RESOLUTION) as f32 * cell_size;
+            let y = (index / RESOLUTION) as f32 * cell_size;
+            instances.push(
+                DrawParam::default()
+                    .dest([rect.x + x, rect.y + y])
+                    .scale([cell_size, cell_size])
+                    .color(*color),
+            );
+        }
+        canvas.draw_instanced_mesh(cell_mesh.clone(), instances, DrawParam::default());
+
+        if let Some(viewport_rect) = self.world_rect_to_minimap(viewport) {
+            if let Ok(outline) =
+                Mesh::new_rectangle(gfx, DrawMode::stroke(1.5), viewport_rect, VIEWPORT_COLOR)
+            {
+                canvas.draw(&outline, DrawParam::default());
+            }
+        }
+    }
+
+    /// The part of the minimap covering `world_rect`, cut to the world bounds.
+    fn world_rect_to_minimap(&self, world_rect: Rect) -> Option<Rect> {
+        let world_size = 2.0 * BOUNDARY_DISTANCE_FROM_CENTER;
+        let to_minimap = |value: f32, start: f32, length: f32| {
+            let fraction = (value + BOUNDARY_DISTANCE_FROM_CENTER) / world_size;
+            start + fraction.clamp(0.0, 1.0) * length
+        };
+        let rect = self.screen_rect;
+        let left = to_minimap(world_rect.left(), rect.x, rect.w);
+        let right = to_minimap(world_rect.right(), rect.x, rect.w);
+        let top = to_minimap(world_rect.top(), rect.y, rect.h);
+        let bottom = to_minimap(world_rect.bottom(), rect.y, rect.h);
+        if right <= left || bottom <= top {
+            return None;
+        }
+        Some(Rect::new(left, top, right - left, bottom - top))
+    }
+}
+
+/// The cell containing `position`, or None if it's outside of the world.
+fn cell_index(position: Point2<f32>) -> Option<usize> {
+    let world_size = 2.0 * BOUNDARY_DISTANCE_FROM_CENTER;
+    let to_cell = |value: f32| {
+        let fraction = (value + BOUNDARY_DISTANCE_FROM_CENTER) / world_size;
+        if !(0.0..=1.0).contains(&fraction) {
+            return None;
+        }
+        Some(((fraction * RESOLUTION as f32) as usize).min(RESOLUTION - 1))
+    };
+    Some(to_cell(position.y)? * RESOLUTION + to_cell(position.x)?)
+}
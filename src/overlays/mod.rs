@@ -0,0 +1,9 @@
+pub mod config_editor;
+pub mod debug_layers;
+pub mod heatmap;
+pub mod minimap;
+pub mod organism_inspector;
+pub mod population_graph;
+pub mod species_legend;
+pub mod terrain_layer;
+pub mod weather_layer;
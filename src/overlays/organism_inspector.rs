@@ -0,0 +1,205 @@
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, Mesh, Rect, Text},
+    mint::Point2,
+};
+
+use crate::{
+    organisms::{organism_details::OrganismDetails, organism_info::OrganismInfo},
+    vector_helper,
+};
+
+const PANEL_SIZE: [f32; 2] = [260.0, 170.0];
+const PANEL_MARGIN: f32 = 10.0;
+const BAR_HEIGHT: f32 = 10.0;
+const PICK_DISTANCE_PX: f32 = 12.0;
+
+/// Keeps track of the organism picked with the mouse and shows what it's up to.
+pub struct OrganismInspector {
+    selected_organism_id: Option<u64>,
+}
+
+impl OrganismInspector {
+    pub fn new() -> Self {
+        Self {
+            selected_organism_id: None,
+        }
+    }
+
+    pub fn selected(&self) -> Option<u64> {
+        self.selected_organism_id
+    }
+
+    pub fn select(&mut self, id: Option<u64>) {
+        self.selected_organism_id = id;
+    }
+
+    /// Selects the organism closest to `world_point`, or nothing if none is close enough.
+    pub fn select_nearest(
+        &mut self,
+        organism_infos: &[OrganismInfo],
+        world_point: Point2<f32>,
+        zoom: f32,
+    ) {
+        let max_distance = PICK_DISTANCE_PX / zoom;
+
+        self.selected_organism_id = organism_infos
+            .iter()
+            .map(|info| {
+                let distance = vector_helper::distance(info.shared_state().position, world_point);
+                (info, distance)
+            })
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(info, _)| info.id());
+    }
+
+    /// Draws a ring around the selected organism,
+    /// along with how far it can see and how close it has to be to eat.
+    pub fn draw_highlight(
+        &self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        info: &OrganismInfo,
+        screen_position: Point2<f32>,
+        zoom: f32,
+    ) {
+        let species = &info.shared_state().species;
+        let organism_radius = info.get_world_size() * zoom * 0.5;
+
+        let circles = [
+            (organism_radius + 4.0, Color::WHITE),
+            (
+                species.eyesight_distance * zoom,
+                Color::new(1.0, 1.0, 0.3, 0.5),
+            ),
+            (
+                species.eating_distance * zoom,
+                Color::new(1.0, 0.5, 0.0, 0.8),
+            ),
+        ];
+
+        for (radius, color) in circles {
+            if radius < 1.0 {
+                continue;
+            }
+            let mesh = Mesh::new_circle(
+                gfx,
+                DrawMode::stroke(1.5),
+                screen_position,
+                radius,
+                (radius * 0.005).max(0.1),
+                color,
+            );
+            if let Ok(mesh) = mesh {
+                canvas.draw(&mesh, DrawParam::default());
+            }
+        }
+    }
+
+    pub fn draw_panel(
+        &self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        display_screen_rect: &Rect,
+        info: Option<&OrganismInfo>,
+        details: Option<&OrganismDetails>,
+    ) {
+        let Some(id) = self.selected_organism_id else {
+            return;
+        };
+
+        let panel_rect = Rect {
+            x: PANEL_MARGIN,
+            y: display_screen_rect.h - PANEL_SIZE[1] - PANEL_MARGIN,
+            w: PANEL_SIZE[0],
+            h: PANEL_SIZE[1],
+        };
+        draw_rectangle(canvas, gfx, panel_rect, Color::new(0.1, 0.1, 0.1, 0.85));
+
+        let text_position = [panel_rect.x + 8.0, panel_rect.y + 6.0];
+
+        let Some(info) = info else {
+            canvas.draw(
+                &Text::new(format!("#{} is gone", id)),
+                DrawParam::default().dest(text_position),
+            );
+            return;
+        };
+
+        let shared_state = info.shared_state();
+        let species = &shared_state.species;
+
+        draw_rectangle(
+            canvas,
+            gfx,
+            Rect::new(text_position[0], text_position[1] + 2.0, 12.0, 12.0),
+            species.color,
+        );
+
+        let mut text = format!("    {} #{}\n", species.name, id);
+        match details {
+            Some(details) => {
+                text += &format!("state: {}\n", details.state_name);
+                if let Some(target_id) = details.hunting_target_id {
+                    text += &format!("hunting: #{}\n", target_id);
+                }
+                if let Some(parent_id) = details.parent_id {
+                    text += &format!("parent: #{}\n", parent_id);
+                }
+            }
+            None => text += "state: ...\n",
+        }
+        text += &format!(
+            "age: {:.0}/{:.0}s",
+            shared_state.age().as_secs_f32(),
+            species.max_age.as_secs_f32()
+        );
+        canvas.draw(&Text::new(text), DrawParam::default().dest(text_position));
+
+        let bars = [
+            (
+                "energy",
+                shared_state.energy(),
+                species.max_energy,
+                Color::YELLOW,
+            ),
+            (
+                "health",
+                shared_state.health,
+                species.max_health,
+                Color::GREEN,
+            ),
+        ];
+        let bar_width = panel_rect.w - 16.0;
+        for (i, (label, value, max, color)) in bars.into_iter().enumerate() {
+            let y = panel_rect.bottom() - (2 - i) as f32 * (BAR_HEIGHT + 16.0);
+            canvas.draw(
+                &Text::new(format!("{} {:.0}/{:.0}", label, value, max)),
+                DrawParam::default().dest([text_position[0], y]),
+            );
+            let bar_rect = Rect::new(text_position[0], y + 14.0, bar_width, BAR_HEIGHT);
+            draw_rectangle(canvas, gfx, bar_rect, Color::new(0.3, 0.3, 0.3, 1.0));
+            let fraction = if max > 0.0 {
+                (value / max).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            draw_rectangle(
+                canvas,
+                gfx,
+                Rect::new(bar_rect.x, bar_rect.y, bar_rect.w * fraction, bar_rect.h),
+                color,
+            );
+        }
+    }
+}
+
+fn draw_rectangle(canvas: &mut Canvas, gfx: &impl Has<GraphicsContext>, rect: Rect, color: Color) {
+    if rect.w <= 0.0 || rect.h <= 0.0 {
+        return;
+    }
+    if let Ok(mesh) = Mesh::new_rectangle(gfx, DrawMode::fill(), rect, color) {
+        canvas.draw(&mesh, DrawParam::default());
+    }
+}
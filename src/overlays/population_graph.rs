@@ -0,0 +1,231 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, Mesh, Rect, Text},
+    mint::Point2,
+};
+
+use crate::simulation_thread::SimulationData;
+
+const DEFAULT_SIZE: [f32; 2] = [420.0, 220.0];
+const MIN_SIZE: [f32; 2] = [120.0, 80.0];
+const MARGIN: f32 = 10.0;
+const RESIZE_HANDLE_SIZE: f32 = 12.0;
+const POINTS_PER_WINDOW: f32 = 400.0;
+const MIN_HISTORY_WINDOW: Duration = Duration::from_secs(10);
+const MAX_HISTORY_WINDOW: Duration = Duration::from_secs(3600);
+
+struct PopulationSample {
+    time: Duration,
+    organism_counter: HashMap<String, u32>,
+}
+
+/// A rolling line chart of the population of each species over simulated time,
+/// drawn in the bottom right corner.
+pub struct PopulationGraph {
+    visible: bool,
+    size: [f32; 2],
+    history_window: Duration,
+    samples: VecDeque<PopulationSample>,
+    /// Colors of every species seen, including extinct ones.
+    species_colors: HashMap<String, Color>,
+    /// Where the graph was drawn last, for hit testing.
+    screen_rect: Rect,
+    resizing: bool,
+}
+
+impl PopulationGraph {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            size: DEFAULT_SIZE,
+            history_window: Duration::from_secs(120),
+            samples: VecDeque::new(),
+            species_colors: HashMap::new(),
+            screen_rect: Rect::default(),
+            resizing: false,
+        }
+    }
+
+    pub fn toggle_visible(&mut self) {
+        self.visible = !self.visible;
+        self.resizing = false;
+    }
+
+    /// Where the graph was drawn last, None if it's hidden.
+    pub fn screen_rect(&self) -> Option<Rect> {
+        self.visible.then_some(self.screen_rect)
+    }
+
+    pub fn history_window(&self) -> Duration {
+        self.history_window
+    }
+
+    pub fn lengthen_history_window(&mut self) {
+        self.history_window = (self.history_window * 2).min(MAX_HISTORY_WINDOW);
+    }
+
+    pub fn shorten_history_window(&mut self) {
+        self.history_window = (self.history_window / 2).max(MIN_HISTORY_WINDOW);
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Records the populations from the snapshot, if enough simulated time passed
+    /// since the last sample for the graph to need another point.
+    pub fn record(&mut self, data: &SimulationData) {
+        if let Some(last) = self.samples.back() {
+            if data.time < last.time {
+                // the simulation was restarted
+                self.samples.clear();
+            } else if data.time - last.time < self.history_window.div_f32(POINTS_PER_WINDOW) {
+                return;
+            }
+        }
+
+        for (species_name, color) in data.species_colors.iter() {
+            self.species_colors.insert(species_name.clone(), *color);
+        }
+
+        self.samples.push_back(PopulationSample {
+            time: data.time,
+            organism_counter: data.organism_counter.clone(),
+        });
+
+        while let Some(first) = self.samples.front() {
+            if data.time - first.time <= MAX_HISTORY_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns whether the click was used by the graph.
+    pub fn mouse_button_down_event(&mut self, x: f32, y: f32) -> bool {
+        if !self.visible {
+            return false;
+        }
+        let handle = Rect::new(
+            self.screen_rect.x,
+            self.screen_rect.y,
+            RESIZE_HANDLE_SIZE,
+            RESIZE_HANDLE_SIZE,
+        );
+        if handle.contains([x, y]) {
+            self.resizing = true;
+        }
+        self.screen_rect.contains([x, y])
+    }
+
+    pub fn mouse_button_up_event(&mut self) {
+        self.resizing = false;
+    }
+
+    /// Returns whether the motion was used by the graph.
+    pub fn mouse_motion_event(&mut self, dx: f32, dy: f32) -> bool {
+        if !self.resizing {
+            return false;
+        }
+        // the graph sticks to the bottom right corner, so it grows up and to the left
+        self.size[0] = (self.size[0] - dx).max(MIN_SIZE[0]);
+        self.size[1] = (self.size[1] - dy).max(MIN_SIZE[1]);
+        true
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        display_screen_rect: &Rect,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        self.screen_rect = Rect::new(
+            display_screen_rect.right() - self.size[0] - MARGIN,
+            display_screen_rect.bottom() - self.size[1] - MARGIN,
+            self.size[0],
+            self.size[1],
+        );
+        let rect = self.screen_rect;
+
+        if let Ok(background) = Mesh::new_rectangle(
+            gfx,
+            DrawMode::fill(),
+            rect,
+            Color::new(0.05, 0.05, 0.05, 0.8),
+        ) {
+            canvas.draw(&background, DrawParam::default());
+        }
+        if let Ok(handle) = Mesh::new_rectangle(
+            gfx,
+            DrawMode::fill(),
+            Rect::new(rect.x, rect.y, RESIZE_HANDLE_SIZE, RESIZE_HANDLE_SIZE),
+            Color::new(0.5, 0.5, 0.5, 0.8),
+        ) {
+            canvas.draw(&handle, DrawParam::default());
+        }
+
+        let Some(latest) = self.samples.back() else {
+            return;
+        };
+        let window_start = latest.time.saturating_sub(self.history_window);
+        let samples_in_window = || self.samples.iter().filter(|s| s.time >= window_start);
+
+        let max_count = samples_in_window()
+            .flat_map(|sample| sample.organism_counter.values())
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let window_s = self.history_window.as_secs_f32();
+        let plot_point = |sample: &PopulationSample, count: u32| Point2 {
+            x: rect.x + (sample.time - window_start).as_secs_f32() / window_s * rect.w,
+            y: rect.bottom() - count as f32 / max_count as f32 * rect.h,
+        };
+
+        let mut species_names: Vec<&String> = latest.organism_counter.keys().collect();
+        species_names.sort();
+
+        for species_name in species_names {
+            let points: Vec<Point2<f32>> = samples_in_window()
+                .map(|sample| {
+                    let count = sample
+                        .organism_counter
+                        .get(species_name)
+                        .copied()
+                        .unwrap_or(0);
+                    plot_point(sample, count)
+                })
+                .collect();
+            if points.len() < 2 {
+                continue;
+            }
+            let color = self
+                .species_colors
+                .get(species_name)
+                .copied()
+                .unwrap_or(Color::WHITE);
+            if let Ok(line) = Mesh::new_line(gfx, &points, 1.5, color) {
+                canvas.draw(&line, DrawParam::default());
+            }
+        }
+
+        canvas.draw(
+            &Text::new(format!("{}", max_count)),
+            DrawParam::default().dest([rect.x + RESIZE_HANDLE_SIZE + 4.0, rect.y]),
+        );
+        canvas.draw(
+            &Text::new(format!("last {:.0}s", window_s)),
+            DrawParam::default().dest([rect.x + 4.0, rect.bottom() - 16.0]),
+        );
+    }
+}
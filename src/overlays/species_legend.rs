@@ -0,0 +1,173 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, Mesh, Rect, Text},
+    input::mouse::MouseButton,
+};
+
+use crate::simulation_thread::SimulationData;
+
+const ROW_HEIGHT: f32 = 18.0;
+const WIDTH: f32 = 220.0;
+const TOP: f32 = 60.0;
+const MARGIN: f32 = 10.0;
+const SWATCH_SIZE: f32 = 12.0;
+/// How far the swatches of hidden species are faded towards grey.
+const HIDDEN_FADE: f32 = 0.8;
+/// How far back in simulated time populations are compared to show a trend.
+const TREND_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Lists every species with its color, count and trend, sorted by name.
+/// Clicking a species hides it, right clicking shows only that species.
+pub struct SpeciesLegend {
+    hidden: HashSet<String>,
+    soloed: Option<String>,
+    /// Changes whenever a species is hidden, shown or soloed.
+    visibility_generation: u64,
+    previous_counts: HashMap<String, u32>,
+    trends: HashMap<String, i64>,
+    last_trend_time: Duration,
+    /// Rows as they were drawn last, for hit testing.
+    rows: Vec<(String, Rect)>,
+}
+
+impl SpeciesLegend {
+    pub fn new() -> Self {
+        Self {
+            hidden: HashSet::new(),
+            soloed: None,
+            visibility_generation: 0,
+            previous_counts: HashMap::new(),
+            trends: HashMap::new(),
+            last_trend_time: Duration::ZERO,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn is_visible(&self, species_name: &str) -> bool {
+        match &self.soloed {
+            Some(soloed) => soloed == species_name,
+            None => !self.hidden.contains(species_name),
+        }
+    }
+
+    /// Changes whenever [`SpeciesLegend::is_visible`] might have changed,
+    /// so that what's computed from it can be cached.
+    pub fn visibility_generation(&self) -> u64 {
+        self.visibility_generation
+    }
+
+    /// Updates the population trends once every [`TREND_INTERVAL`] of simulated time.
+    pub fn record(&mut self, data: &SimulationData) {
+        if data.time < self.last_trend_time {
+            // the simulation was restarted
+            self.previous_counts.clear();
+            self.trends.clear();
+            self.last_trend_time = data.time;
+        }
+        if data.time - self.last_trend_time < TREND_INTERVAL {
+            return;
+        }
+
+        self.trends = data
+            .organism_counter
+            .iter()
+            .map(|(species_name, count)| {
+                let previous = self.previous_counts.get(species_name).copied();
+                let trend = previous.map_or(0, |previous| *count as i64 - previous as i64);
+                (species_name.clone(), trend)
+            })
+            .collect();
+        self.previous_counts.clone_from(&data.organism_counter);
+        self.last_trend_time = data.time;
+    }
+
+    /// Returns whether the click was used by the legend.
+    pub fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) -> bool {
+        let Some((species_name, _)) = self.rows.iter().find(|(_, rect)| rect.contains([x, y]))
+        else {
+            return false;
+        };
+        let species_name = species_name.clone();
+
+        match button {
+            MouseButton::Left => {
+                self.soloed = None;
+                if !self.hidden.remove(&species_name) {
+                    self.hidden.insert(species_name);
+                }
+            }
+            MouseButton::Right => {
+                if self.soloed.as_ref() == Some(&species_name) {
+                    self.soloed = None;
+                } else {
+                    self.soloed = Some(species_name);
+                }
+            }
+            _ => return false,
+        }
+        self.visibility_generation += 1;
+        true
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        display_screen_rect: &Rect,
+        data: &SimulationData,
+    ) {
+        let mut species: Vec<(&String, &u32)> = data.organism_counter.iter().collect();
+        species.sort_by_key(|(species_name, _)| *species_name);
+
+        let left = display_screen_rect.right() - WIDTH - MARGIN;
+        self.rows.clear();
+
+        for (i, (species_name, count)) in species.into_iter().enumerate() {
+            let row_rect = Rect::new(left, TOP + i as f32 * ROW_HEIGHT, WIDTH, ROW_HEIGHT);
+            self.rows.push((species_name.clone(), row_rect));
+
+            let visible = self.is_visible(species_name);
+            let mut color = data
+                .species_colors
+                .get(species_name)
+                .copied()
+                .unwrap_or(Color::WHITE);
+            let mut text_color = Color::WHITE;
+            if !visible {
+                // the frame is drawn replacing colors, so fading has to happen in the color itself
+                let grey = 0.5;
+                color.r += (grey - color.r) * HIDDEN_FADE;
+                color.g += (grey - color.g) * HIDDEN_FADE;
+                color.b += (grey - color.b) * HIDDEN_FADE;
+                text_color = Color::new(grey, grey, grey, 1.0);
+            }
+
+            let swatch = Rect::new(
+                row_rect.x,
+                row_rect.y + (ROW_HEIGHT - SWATCH_SIZE) * 0.5,
+                SWATCH_SIZE,
+                SWATCH_SIZE,
+            );
+            if let Ok(mesh) = Mesh::new_rectangle(gfx, DrawMode::fill(), swatch, color) {
+                canvas.draw(&mesh, DrawParam::default());
+            }
+
+            let trend = match self.trends.get(species_name).copied().unwrap_or(0) {
+                t if t > 0 => "↑",
+                t if t < 0 => "↓",
+                _ => "→",
+            };
+            canvas.draw(
+                &Text::new(format!("{} {} {}", species_name, count, trend)),
+                DrawParam::default()
+                    .dest([swatch.right() + 6.0, row_rect.y])
+                    .color(text_color),
+            );
+        }
+    }
+}
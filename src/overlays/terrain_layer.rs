@@ -0,0 +1,143 @@
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, InstanceArray, Mesh, Rect},
+    mint::Point2,
+};
+
+use crate::{
+    configurations::terrain_configuration::{Biome, Obstacle},
+    simulation_thread::SimulationData,
+};
+
+const OBSTACLE_COLOR: Color = Color::new(0.35, 0.35, 0.38, 1.0);
+
+/// The biomes and obstacles of the terrain, drawn under everything else in the world.
+pub struct TerrainLayer {
+    visible: bool,
+    cell_mesh: Option<Mesh>,
+    circle_mesh: Option<Mesh>,
+    instances: Option<InstanceArray>,
+    circle_instances: Option<InstanceArray>,
+}
+
+impl TerrainLayer {
+    pub fn new() -> Self {
+        Self {
+            visible: true,
+            cell_mesh: None,
+            circle_mesh: None,
+            instances: None,
+            circle_instances: None,
+        }
+    }
+
+    pub fn toggle_visible(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        data: &SimulationData,
+        world_to_screen: impl Fn(Point2<f32>) -> Point2<f32>,
+    ) {
+        if !self.visible || (data.terrain.is_flat() && data.terrain.obstacles().is_empty()) {
+            return;
+        }
+
+        let display_screen_rect = canvas.screen_coordinates().unwrap();
+        let cell_mesh = self.cell_mesh.get_or_insert_with(|| {
+            Mesh::new_rectangle(
+                gfx,
+                DrawMode::fill(),
+                Rect::new(0.0, 0.0, 1.0, 1.0),
+                Color::WHITE,
+            )
+            .unwrap()
+        });
+        let instances = self
+            .instances
+            .get_or_insert_with(|| InstanceArray::new(gfx, None));
+        instances.clear();
+
+        let cells = data
+            .terrain
+            .cells()
+            .filter(|_| !data.terrain.is_flat())
+            .map(|(world_rect, biome)| (world_rect, biome_color(biome)));
+        let rectangles = data.terrain.obstacles().iter().filter_map(|obstacle| {
+            if let Obstacle::Rectangle { x, y, w, h } = *obstacle {
+                Some((Rect::new(x, y, w, h), OBSTACLE_COLOR))
+            } else {
+                None
+            }
+        });
+        for (world_rect, color) in cells.chain(rectangles) {
+            let top_left = world_to_screen(world_rect.point());
+            let bottom_right = world_to_screen(Point2 {
+                x: world_rect.right(),
+                y: world_rect.bottom(),
+            });
+            let screen_rect = Rect::new(
+                top_left.x,
+                top_left.y,
+                bottom_right.x - top_left.x,
+                bottom_right.y - top_left.y,
+            );
+            if !screen_rect.overlaps(&display_screen_rect) {
+                continue;
+            }
+            instances.push(
+                DrawParam::default()
+                    .dest(top_left)
+                    .scale([screen_rect.w, screen_rect.h])
+                    .color(color),
+            );
+        }
+        canvas.draw_instanced_mesh(cell_mesh.clone(), instances, DrawParam::default());
+
+        let circle_mesh = self.circle_mesh.get_or_insert_with(|| {
+            Mesh::new_circle(
+                gfx,
+                DrawMode::fill(),
+                Point2 { x: 0.0, y: 0.0 },
+                1.0,
+                0.001,
+                Color::WHITE,
+            )
+            .unwrap()
+        });
+        let circle_instances = self
+            .circle_instances
+            .get_or_insert_with(|| InstanceArray::new(gfx, None));
+        circle_instances.clear();
+        for obstacle in data.terrain.obstacles() {
+            if let Obstacle::Circle { center, radius } = *obstacle {
+                let screen_center = world_to_screen(center.into());
+                let screen_edge = world_to_screen(Point2 {
+                    x: center[0] + radius,
+                    y: center[1],
+                });
+                let screen_radius = screen_edge.x - screen_center.x;
+                circle_instances.push(
+                    DrawParam::default()
+                        .dest(screen_center)
+                        .scale([screen_radius, screen_radius])
+                        .color(OBSTACLE_COLOR),
+                );
+            }
+        }
+        canvas.draw_instanced_mesh(circle_mesh.clone(), circle_instances, DrawParam::default());
+    }
+}
+
+/// Dark colors, so the organisms stand out.
+fn biome_color(biome: Biome) -> Color {
+    match biome {
+        Biome::Grassland => Color::from_rgb(24, 42, 22),
+        Biome::Desert => Color::from_rgb(58, 50, 30),
+        Biome::Water => Color::from_rgb(16, 30, 58),
+        Biome::Forest => Color::from_rgb(12, 32, 16),
+    }
+}
@@ -0,0 +1,87 @@
+use ggez::{
+    context::Has,
+    graphics::{Canvas, Color, DrawMode, DrawParam, GraphicsContext, InstanceArray, Mesh, Rect},
+    mint::Point2,
+};
+
+use crate::simulation_thread::SimulationData;
+
+const FLOOD_COLOR: Color = Color::new(0.1, 0.25, 0.6, 1.0);
+const FIRE_COLOR: Color = Color::new(0.9, 0.35, 0.05, 1.0);
+
+/// Floods and fires, drawn over the terrain while they last.
+pub struct WeatherLayer {
+    cell_mesh: Option<Mesh>,
+    instances: Option<InstanceArray>,
+}
+
+impl WeatherLayer {
+    pub fn new() -> Self {
+        Self {
+            cell_mesh: None,
+            instances: None,
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas,
+        gfx: &impl Has<GraphicsContext>,
+        data: &SimulationData,
+        world_to_screen: impl Fn(Point2<f32>) -> Point2<f32>,
+    ) {
+        let weather = &data.weather;
+        if weather.flood_level.is_none() && weather.burning.is_empty() {
+            return;
+        }
+
+        let display_screen_rect = canvas.screen_coordinates().unwrap();
+        let cell_mesh = self.cell_mesh.get_or_insert_with(|| {
+            Mesh::new_rectangle(
+                gfx,
+                DrawMode::fill(),
+                Rect::new(0.0, 0.0, 1.0, 1.0),
+                Color::WHITE,
+            )
+            .unwrap()
+        });
+        let instances = self
+            .instances
+            .get_or_insert_with(|| InstanceArray::new(gfx, None));
+        instances.clear();
+
+        let flooded = data
+            .terrain
+            .elevations()
+            .filter(|(_, elevation)| weather.flood_level.is_some_and(|level| *elevation < level))
+            .map(|(world_rect, _)| (world_rect, FLOOD_COLOR));
+        let burning = weather
+            .burning
+            .iter()
+            .map(|world_rect| (*world_rect, FIRE_COLOR));
+        for (world_rect, color) in flooded.chain(burning) {
+            let top_left = world_to_screen(world_rect.point());
+            let bottom_right = world_to_screen(Point2 {
+                x: world_rect.right(),
+                y: world_rect.bottom(),
+            });
+            let screen_rect = Rect::new(
+                top_left.x,
+                top_left.y,
+                bottom_right.x - top_left.x,
+                bottom_right.y - top_left.y,
+            );
+            if !screen_rect.overlaps(&display_screen_rect) {
+                continue;
+            }
+            instances.push(
+                DrawParam::default()
+                    .dest(top_left)
+                    .scale([screen_rect.w, screen_rect.h])
+                    .color(color),
+            );
+        }
+
+        canvas.draw_instanced_mesh(cell_mesh.clone(), instances, DrawParam::default());
+    }
+}
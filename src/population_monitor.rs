@@ -0,0 +1,261 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use crate::{
+    configurations::detection_configuration::DetectionConfiguration, organisms::species::Species,
+    simulation_event::SimulationEventKind,
+};
+
+/// Watches the populations of the configured species for extinctions, collapses,
+/// steady states and limit cycles. Every condition is reported once when it starts.
+pub struct PopulationMonitor {
+    config: DetectionConfiguration,
+    species: Vec<TrackedSpecies>,
+    next_sample: Duration,
+    collapsed: bool,
+    steady: bool,
+    cycling: bool,
+}
+
+struct TrackedSpecies {
+    name: String,
+    /// The samples in the window, oldest first.
+    samples: VecDeque<u32>,
+    /// Species that were never alive can't go extinct.
+    was_alive: bool,
+    extinct: bool,
+}
+
+impl PopulationMonitor {
+    pub fn new(config: DetectionConfiguration) -> Self {
+        Self {
+            config,
+            species: Vec::new(),
+            next_sample: Duration::ZERO,
+            collapsed: false,
+            steady: false,
+            cycling: false,
+        }
+    }
+
+    /// Looks at the populations at `time` and returns what started happening.
+    pub fn observe(
+        &mut self,
+        time: Duration,
+        species: &[Species],
+        organism_counter: &HashMap<String, u32>,
+    ) -> Vec<SimulationEventKind> {
+        for configured in species {
+            if !self
+                .species
+                .iter()
+                .any(|tracked| tracked.name == configured.name)
+            {
+                self.species.push(TrackedSpecies {
+                    name: configured.name.clone(),
+                    samples: VecDeque::new(),
+                    was_alive: false,
+                    extinct: false,
+                });
+            }
+        }
+
+        let mut events = Vec::new();
+        let mut total = 0;
+        for tracked in self.species.iter_mut() {
+            let population = organism_counter.get(&tracked.name).copied().unwrap_or(0);
+            total += population;
+            if population > 0 {
+                tracked.was_alive = true;
+                tracked.extinct = false;
+            } else if tracked.was_alive && !tracked.extinct {
+                tracked.extinct = true;
+                events.push(SimulationEventKind::Extinction {
+                    species: tracked.name.clone(),
+                });
+            }
+        }
+
+        let collapsed = total <= self.config.collapse_population;
+        if collapsed && !self.collapsed {
+            events.push(SimulationEventKind::Collapse);
+        }
+        self.collapsed = collapsed;
+
+        if time < self.next_sample {
+            return events;
+        }
+        while self.next_sample <= time {
+            self.next_sample += self.config.sample_interval;
+        }
+
+        let window_samples = self.config.window_samples();
+        for tracked in self.species.iter_mut() {
+            let population = organism_counter.get(&tracked.name).copied().unwrap_or(0);
+            tracked.samples.push_back(population);
+            while tracked.samples.len() > window_samples {
+                tracked.samples.pop_front();
+            }
+        }
+        if collapsed
+            || self
+                .species
+                .iter()
+                .any(|tracked| tracked.samples.len() < window_samples)
+        {
+            return events;
+        }
+
+        let steady = self
+            .species
+            .iter()
+            .all(|tracked| is_steady(&tracked.samples, self.config.steady_state_tolerance));
+        if steady && !self.steady {
+            events.push(SimulationEventKind::SteadyState);
+        }
+        self.steady = steady;
+
+        let period = if steady { None } else { self.cycle_period() };
+        if let Some(period) = period {
+            if !self.cycling {
+                events.push(SimulationEventKind::LimitCycle {
+                    period: self.config.sample_interval * period as u32,
+                });
+            }
+        }
+        self.cycling = period.is_some();
+
+        events
+    }
+
+    /// The period of the cycle the populations are in, in samples.
+    /// It's the first peak of their average autocorrelation
+    /// after it went negative, so that trends don't count as cycles.
+    fn cycle_period(&self) -> Option<usize> {
+        let varying: Vec<Vec<f64>> = self
+            .species
+            .iter()
+            .map(|tracked| {
+                tracked
+                    .samples
+                    .iter()
+                    .map(|&sample| sample as f64)
+                    .collect()
+            })
+            .filter(|samples: &Vec<f64>| samples.iter().any(|&sample| sample != samples[0]))
+            .collect();
+        if varying.is_empty() {
+            return None;
+        }
+
+        let max_lag = varying[0].len() / 2;
+        let correlations: Vec<f64> = (0..=max_lag + 1)
+            .map(|lag| {
+                varying
+                    .iter()
+                    .map(|samples| autocorrelation(samples, lag))
+                    .sum::<f64>()
+                    / varying.len() as f64
+            })
+            .collect();
+
+        let first_negative = correlations.iter().position(|&r| r < 0.0)?;
+        (first_negative.max(2)..=max_lag).find(|&lag| {
+            correlations[lag] >= self.config.cycle_correlation as f64
+                && correlations[lag] >= correlations[lag - 1]
+                && correlations[lag] >= correlations[lag + 1]
+        })
+    }
+}
+
+fn is_steady(samples: &VecDeque<u32>, tolerance: f32) -> bool {
+    let count = samples.len() as f64;
+    let mean = samples.iter().map(|&sample| sample as f64).sum::<f64>() / count;
+    if mean == 0.0 {
+        return true;
+    }
+    let variance = samples
+        .iter()
+        .map(|&sample| (sample as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    variance.sqrt() / mean <= tolerance as f64
+}
+
+/// How similar `samples` is to itself shifted by `lag`, from -1 to 1.
+fn autocorrelation(samples: &[f64], lag: usize) -> f64 {
+    let count = samples.len();
+    if lag >= count {
+        return 0.0;
+    }
+    let mean = samples.iter().sum::<f64>() / count as f64;
+    let variance = samples
+        .iter()
+        .map(|sample| (sample - mean).powi(2))
+        .sum::<f64>()
+        / count as f64;
+    let covariance = (0..count - lag)
+        .map(|t| (samples[t] - mean) * (samples[t + lag] - mean))
+        .sum::<f64>()
+        / (count - lag) as f64;
+    covariance / variance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Observes one species with `population(sample)` once per second for `samples` seconds.
+    fn observe_series(
+        population: impl Fn(usize) -> u32,
+        samples: usize,
+    ) -> Vec<SimulationEventKind> {
+        let species = vec![
+            crate::generate_default_generation_configuration().species[0]
+                .species
+                .clone(),
+        ];
+        let mut monitor = PopulationMonitor::new(DetectionConfiguration::default());
+        let mut events = Vec::new();
+        for sample in 0..samples {
+            let counter = HashMap::from([(species[0].name.clone(), population(sample))]);
+            events.extend(monitor.observe(Duration::from_secs(sample as u64), &species, &counter));
+        }
+        events
+    }
+
+    fn is_cycle(event: &SimulationEventKind) -> bool {
+        matches!(event, SimulationEventKind::LimitCycle { .. })
+    }
+
+    #[test]
+    fn steady_series_is_a_steady_state() {
+        let events = observe_series(|sample| 100 + (sample % 3) as u32, 120);
+        assert!(events.contains(&SimulationEventKind::SteadyState));
+        assert!(!events.iter().any(is_cycle));
+    }
+
+    #[test]
+    fn sine_series_is_a_cycle_with_its_period() {
+        let events = observe_series(
+            |sample| {
+                let phase = sample as f32 / 20.0 * std::f32::consts::TAU;
+                (100.0 + 50.0 * phase.sin()).round() as u32
+            },
+            120,
+        );
+        assert!(events.contains(&SimulationEventKind::LimitCycle {
+            period: Duration::from_secs(20)
+        }));
+        assert!(!events.contains(&SimulationEventKind::SteadyState));
+    }
+
+    #[test]
+    fn trend_is_neither_steady_nor_a_cycle() {
+        let events = observe_series(|sample| 10 + 2 * sample as u32, 120);
+        assert!(!events.contains(&SimulationEventKind::SteadyState));
+        assert!(!events.iter().any(is_cycle));
+    }
+}
@@ -0,0 +1,40 @@
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, Error, RngCore, SeedableRng};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Makes the random numbers of this thread repeat for the same `seed`,
+/// so a simulation run on it can be reproduced.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// The random number generator the simulation uses, like [`rand::thread_rng`]
+/// but seedable with [`seed`].
+pub fn rng() -> SimulationRng {
+    SimulationRng
+}
+
+#[derive(Clone, Copy)]
+pub struct SimulationRng;
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
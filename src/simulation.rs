@@ -1,67 +1,321 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
     time::Duration,
 };
 
-use rand::{distributions::Uniform, prelude::Distribution};
+use ggez::mint::Point2;
+use rand::{distributions::Uniform, prelude::Distribution, seq::IteratorRandom, Rng};
 
 use crate::{
-    configurations::generation_configuration::GenerationConfiguration,
+    clock::Clock,
+    configurations::{
+        detection_configuration::DetectionConfiguration,
+        generation_configuration::GenerationConfiguration,
+        scenario::{Scenario, ScenarioAction, ScenarioEvent},
+    },
     environment_awareness::EnvironmentAwareness,
-    organisms::{organism::Organism, organism_info::OrganismInfo, organism_result::OrganismResult},
+    nutrient_field::NutrientField,
+    organisms::{
+        organism::Organism, organism_details::OrganismDetails, organism_info::OrganismInfo,
+        organism_result::OrganismResult, species::Species,
+    },
+    population_monitor::PopulationMonitor,
+    random,
+    simulation_event::SimulationEvent,
     simulation_thread::SimulationData,
+    terrain::Terrain,
     vector_helper,
+    weather::Weather,
 };
 
-const BOUNDARY_DISTANCE_FROM_CENTER: f32 = 100f32;
+pub const BOUNDARY_DISTANCE_FROM_CENTER: f32 = 100f32;
 const WORLD_SIZE: f32 =
     (2.0 * BOUNDARY_DISTANCE_FROM_CENTER) * (2.0 * BOUNDARY_DISTANCE_FROM_CENTER);
+/// How many times a new organism is placed somewhere else when it lands on impassable terrain.
+const MAX_PLACEMENT_TRIES: u32 = 20;
 
 pub struct Simulation {
     organisms: Vec<Organism>,
+    /// The configured species, for organisms added after the generation.
+    species: Vec<Species>,
     to_add: Vec<Organism>,
     to_remove: HashSet<u64>,
     environment_awareness: EnvironmentAwareness,
+    nutrients: NutrientField,
+    clock: Clock,
+    weather: Weather,
     cull_organisms_outside_view: bool,
-    pub simulation_data: SimulationData,
+    organism_counter: HashMap<String, u32>,
+    /// Scenario events that didn't happen yet, the earliest first.
+    scenario_events: VecDeque<ScenarioEvent>,
+    drought: Option<Drought>,
+    population_monitor: PopulationMonitor,
+    /// Everything noteworthy that happened so far, the oldest first.
+    events: Vec<SimulationEvent>,
+    time: Duration,
+    step: u64,
+}
+
+struct Drought {
+    until: Duration,
+    /// The photosynthesis rates the species get back when the drought ends.
+    saved_rates: HashMap<String, f32>,
 }
 
 impl Simulation {
     pub fn new(generation_configuration: &GenerationConfiguration) -> Self {
-        let organisms = Self::generate_organisms(generation_configuration);
+        let terrain = Terrain::new(&generation_configuration.terrain).unwrap_or_else(|error| {
+            println!("{}, using flat terrain", error);
+            Terrain::flat(&generation_configuration.terrain)
+        });
+        let organisms = Self::generate_organisms(generation_configuration, &terrain);
         let mut organism_counter = HashMap::new();
         for organism in organisms.iter() {
             Self::adjust_species_counter(organism, &mut organism_counter, true, 1);
         }
-        let organism_infos = OrganismInfo::new_from_organisms(&organisms);
 
         Simulation {
             organisms,
+            species: generation_configuration
+                .species
+                .iter()
+                .map(|species_config| species_config.species.clone())
+                .collect(),
             to_add: Vec::new(),
             to_remove: HashSet::new(),
-            environment_awareness: EnvironmentAwareness::new(32.0),
+            environment_awareness: EnvironmentAwareness::new(32.0, Arc::new(terrain)),
+            nutrients: NutrientField::new(&generation_configuration.nutrients),
+            clock: Clock::new(&generation_configuration.clock),
+            weather: Weather::new(&generation_configuration.weather),
             cull_organisms_outside_view: false,
-            simulation_data: SimulationData {
-                organism_infos,
-                organism_counter,
-                time: Duration::ZERO,
-                step: 0,
-            },
+            organism_counter,
+            scenario_events: VecDeque::new(),
+            drought: None,
+            population_monitor: PopulationMonitor::new(DetectionConfiguration::default()),
+            events: Vec::new(),
+            time: Duration::ZERO,
+            step: 0,
         }
     }
 
+    /// Schedules the events of `scenario` that are still in the future.
+    pub fn set_scenario(&mut self, scenario: &Scenario) {
+        let mut events: Vec<ScenarioEvent> = scenario
+            .events
+            .iter()
+            .filter(|event| event.time >= self.time)
+            .cloned()
+            .collect();
+        // stable, so events at the same time happen in the order they were written in
+        events.sort_by_key(|event| event.time);
+        self.scenario_events = events.into();
+    }
+
+    pub fn pending_scenario_events(&self) -> usize {
+        self.scenario_events.len()
+    }
+
+    /// Changes what counts as a collapse, steady state or limit cycle from now on.
+    pub fn set_detection_configuration(&mut self, config: DetectionConfiguration) {
+        self.population_monitor = PopulationMonitor::new(config);
+    }
+
+    pub fn events(&self) -> &[SimulationEvent] {
+        &self.events
+    }
+
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// How many organisms of each species are alive, corpses included.
+    pub fn organism_counter(&self) -> &HashMap<String, u32> {
+        &self.organism_counter
+    }
+
+    /// Gives living organisms the parameters of the species with the same name.
+    /// Species that aren't in `species` stay as they are.
+    pub fn update_species(&mut self, species: &[Species]) {
+        // during a drought the new rates only apply once it's over
+        let Some(drought) = self.drought.as_mut() else {
+            self.apply_species(species);
+            return;
+        };
+        let species: Vec<Species> = species
+            .iter()
+            .map(|updated| {
+                drought
+                    .saved_rates
+                    .insert(updated.name.clone(), updated.photosynthesis_rate_s);
+                Species {
+                    photosynthesis_rate_s: 0.0,
+                    ..updated.clone()
+                }
+            })
+            .collect();
+        self.apply_species(&species);
+    }
+
+    /// Like [`Simulation::update_species`], but ignoring droughts.
+    fn apply_species(&mut self, species: &[Species]) {
+        for updated in species {
+            match self.species.iter_mut().find(|old| old.name == updated.name) {
+                Some(old) => *old = updated.clone(),
+                None => self.species.push(updated.clone()),
+            }
+        }
+
+        for organism in self.organisms.iter_mut() {
+            let updated = species
+                .iter()
+                .find(|species| species.name == organism.shared_state.species.name);
+            if let Some(updated) = updated {
+                organism.shared_state.species = updated.clone();
+            }
+        }
+    }
+
+    /// Adds `amount` newborn organisms of a configured species,
+    /// scattered evenly within `radius` of `center`.
+    pub fn spawn_organisms(
+        &mut self,
+        species_name: &str,
+        center: Point2<f32>,
+        radius: f32,
+        amount: u32,
+    ) {
+        let Some(species) = self.species.iter().find(|s| s.name == species_name) else {
+            return;
+        };
+
+        for _ in 0..amount {
+            let position = passable_position(self.environment_awareness.terrain(), || {
+                // the square root keeps the organisms from bunching up in the center
+                let distance = radius * random::rng().gen::<f32>().sqrt();
+                let angle = random::rng().gen::<f32>() * std::f32::consts::TAU;
+                let offset =
+                    vecmath::vec2_scale(vector_helper::create_direction_vector(angle), distance);
+                Point2 {
+                    x: center.x + offset[0],
+                    y: center.y + offset[1],
+                }
+            });
+
+            let mut organism = Organism::new(species.clone());
+            organism.set_position_x_y(position.x, position.y);
+            Self::adjust_species_counter(&organism, &mut self.organism_counter, true, 1);
+            self.organisms.push(organism);
+        }
+    }
+
+    /// Removes every organism within `radius` of `center`, corpses included.
+    pub fn remove_organisms(&mut self, center: Point2<f32>, radius: f32) {
+        let organism_counter = &mut self.organism_counter;
+        self.organisms.retain(|organism| {
+            if vector_helper::distance(organism.position(), center) > radius {
+                return true;
+            }
+            Self::adjust_species_counter(organism, organism_counter, false, 1);
+            false
+        });
+    }
+
+    pub fn organism_details(&self, id: u64) -> Option<OrganismDetails> {
+        self.organisms
+            .iter()
+            .find(|organism| organism.id() == id)
+            .map(|organism| organism.details())
+    }
+
+    /// Overwrites `snapshot` with the current state of the simulation,
+    /// reusing its allocations.
+    pub fn write_snapshot(&self, snapshot: &mut SimulationData) {
+        OrganismInfo::from_organisms_fill_vec(&self.organisms, &mut snapshot.organism_infos);
+        snapshot.organism_counter.clone_from(&self.organism_counter);
+        let species_names = self.species.iter().map(|species| &species.name);
+        if !snapshot.species_names.iter().eq(species_names.clone()) {
+            snapshot.species_names = species_names.cloned().collect();
+        }
+        snapshot.species_colors.clear();
+        for organism in self.organisms.iter() {
+            let species = &organism.shared_state().species;
+            if !snapshot.species_colors.contains_key(&species.name) {
+                snapshot
+                    .species_colors
+                    .insert(species.name.clone(), species.color);
+            }
+        }
+        snapshot.chunk_occupancy.clear();
+        for (index, chunk) in self.environment_awareness.get_chunks() {
+            snapshot.chunk_occupancy.push((
+                self.environment_awareness.get_chunk_coordinates(*index),
+                chunk.len(),
+            ));
+        }
+        if !Arc::ptr_eq(&snapshot.terrain, self.environment_awareness.terrain_arc()) {
+            snapshot.terrain = self.environment_awareness.terrain_arc().clone();
+        }
+        snapshot.nutrients.clone_from(&self.nutrients);
+        snapshot.clock = self.clock.is_enabled().then(|| self.clock.read(self.time));
+        snapshot.weather = self.weather.reading(self.time);
+        snapshot.pending_scenario_events = self.scenario_events.len();
+        snapshot.events.clone_from(&self.events);
+        snapshot.time = self.time;
+        snapshot.step = self.step;
+    }
+
+    /// Runs one step of `delta`. Scenario events falling inside of it split it,
+    /// so that they happen exactly at their time no matter the step length.
     pub fn run(&mut self, delta: Duration) {
+        let end_time = self.time + delta;
+        loop {
+            self.run_due_events();
+            match self.next_scheduled_time() {
+                Some(time) if time < end_time => self.advance(time - self.time),
+                _ => {
+                    self.advance(end_time - self.time);
+                    break;
+                }
+            }
+        }
+        self.step += 1;
+        self.cull_organisms_outside_view = false;
+
+        let time = self.time;
+        let new_events =
+            self.population_monitor
+                .observe(time, &self.species, &self.organism_counter);
+        self.events.extend(
+            new_events
+                .into_iter()
+                .map(|kind| SimulationEvent { time, kind }),
+        );
+    }
+
+    fn advance(&mut self, delta: Duration) {
+        if delta.is_zero() {
+            return;
+        }
         self.environment_awareness.refill(&self.organisms);
+        self.environment_awareness
+            .set_clock(self.clock.read(self.time));
+        self.environment_awareness
+            .set_weather_photosynthesis(self.weather.photosynthesis());
         for organism in self.organisms.iter_mut() {
-            match Self::simulate_organism(organism, delta, &self.environment_awareness) {
+            match Self::simulate_organism(
+                organism,
+                delta,
+                &self.environment_awareness,
+                &mut self.nutrients,
+            ) {
                 OrganismsChange::Add(mut vec) => {
                     vec.iter().for_each(|x| {
-                        Self::adjust_species_counter(
-                            x,
-                            &mut self.simulation_data.organism_counter,
-                            true,
-                            1,
-                        )
+                        Self::adjust_species_counter(x, &mut self.organism_counter, true, 1)
                     });
                     self.to_add.append(&mut vec);
                 }
@@ -70,12 +324,7 @@ impl Simulation {
                 }
                 OrganismsChange::AddRemove(mut vec, id) => {
                     vec.iter().for_each(|x| {
-                        Self::adjust_species_counter(
-                            x,
-                            &mut self.simulation_data.organism_counter,
-                            true,
-                            1,
-                        )
+                        Self::adjust_species_counter(x, &mut self.organism_counter, true, 1)
                     });
                     self.to_add.append(&mut vec);
                     self.to_remove.insert(id);
@@ -83,36 +332,181 @@ impl Simulation {
                 OrganismsChange::None => {}
             };
         }
+
+        let end_time = self.time + delta;
+        let outcome = self.weather.step(
+            end_time,
+            delta,
+            &self.organisms,
+            &self.to_remove,
+            self.environment_awareness.terrain(),
+        );
+        for id in outcome.killed {
+            let Some(organism) = self.organisms.iter().find(|organism| organism.id() == id) else {
+                continue;
+            };
+            if let OrganismResult::Died = organism.die(&mut self.nutrients) {
+                let corpse = Organism::new_corpse(organism);
+                Self::adjust_species_counter(&corpse, &mut self.organism_counter, true, 1);
+                self.to_add.push(corpse);
+            }
+            self.to_remove.insert(id);
+        }
+        self.events
+            .extend(outcome.events.into_iter().map(|kind| SimulationEvent {
+                time: end_time,
+                kind,
+            }));
+
         self.organisms.retain(|x| {
             if !self.to_remove.contains(&x.id()) {
                 true
             } else {
-                Self::adjust_species_counter(
-                    x,
-                    &mut self.simulation_data.organism_counter,
-                    false,
-                    1,
-                );
+                Self::adjust_species_counter(x, &mut self.organism_counter, false, 1);
                 false
             }
         });
         self.organisms.append(&mut self.to_add);
-        self.simulation_data.step += 1;
-        self.simulation_data.time += delta;
-        self.cull_organisms_outside_view = false;
+        self.nutrients.diffuse(delta);
+        self.time += delta;
+    }
 
-        OrganismInfo::from_organisms_fill_vec(
-            &self.organisms,
-            &mut self.simulation_data.organism_infos,
-        );
+    /// When the next scenario event happens or the drought ends.
+    fn next_scheduled_time(&self) -> Option<Duration> {
+        let next_event = self.scenario_events.front().map(|event| event.time);
+        let drought_end = self.drought.as_ref().map(|drought| drought.until);
+        match (next_event, drought_end) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn run_due_events(&mut self) {
+        if self
+            .drought
+            .as_ref()
+            .is_some_and(|drought| drought.until <= self.time)
+        {
+            self.end_drought();
+        }
+
+        while self
+            .scenario_events
+            .front()
+            .is_some_and(|event| event.time <= self.time)
+        {
+            if let Some(event) = self.scenario_events.pop_front() {
+                self.run_scenario_action(event.action);
+            }
+        }
+    }
+
+    fn run_scenario_action(&mut self, action: ScenarioAction) {
+        match action {
+            ScenarioAction::Introduce {
+                species,
+                amount,
+                center,
+                radius,
+            } => self.spawn_organisms(&species, center.into(), radius, amount),
+            ScenarioAction::Cull { species, percent } => self.cull_species(&species, percent),
+            ScenarioAction::ChangeParameter {
+                species,
+                parameter,
+                value,
+            } => self.change_species_parameter(&species, &parameter, value),
+            ScenarioAction::Drought { duration } => self.start_drought(duration),
+        }
+    }
+
+    /// Removes a random `percent` of the organisms of a species.
+    fn cull_species(&mut self, species_name: &str, percent: f32) {
+        let of_species = self
+            .organisms
+            .iter()
+            .filter(|organism| organism.shared_state().species.name == species_name);
+        let amount = (of_species.clone().count() as f32 * percent / 100.0).round() as usize;
+        let culled: HashSet<u64> = of_species
+            .map(|organism| organism.id())
+            .choose_multiple(&mut random::rng(), amount)
+            .into_iter()
+            .collect();
+
+        let organism_counter = &mut self.organism_counter;
+        self.organisms.retain(|organism| {
+            if !culled.contains(&organism.id()) {
+                return true;
+            }
+            Self::adjust_species_counter(organism, organism_counter, false, 1);
+            false
+        });
+    }
+
+    /// Changes a parameter of a species and its living organisms.
+    fn change_species_parameter(&mut self, species_name: &str, parameter: &str, value: f32) {
+        // during a drought the new rate only applies once it's over
+        if parameter == "photosynthesis_rate_s" {
+            if let Some(drought) = self.drought.as_mut() {
+                drought.saved_rates.insert(species_name.to_string(), value);
+                return;
+            }
+        }
+
+        let Some(species) = self.species.iter_mut().find(|s| s.name == species_name) else {
+            return;
+        };
+        if let Err(error) = species.set_parameter(parameter, value) {
+            println!("Scenario event failed: {}", error);
+            return;
+        }
+        let species = species.clone();
+        self.apply_species(&[species]);
+    }
+
+    fn start_drought(&mut self, duration: Duration) {
+        let until = self.time + duration;
+        if let Some(drought) = self.drought.as_mut() {
+            drought.until = drought.until.max(until);
+            return;
+        }
+
+        let saved_rates = self
+            .species
+            .iter()
+            .map(|species| (species.name.clone(), species.photosynthesis_rate_s))
+            .collect();
+        self.drought = Some(Drought { until, saved_rates });
+        self.set_photosynthesis_rates(|_| 0.0);
+    }
+
+    fn end_drought(&mut self) {
+        let Some(drought) = self.drought.take() else {
+            return;
+        };
+        self.set_photosynthesis_rates(|species_name| {
+            drought
+                .saved_rates
+                .get(species_name)
+                .copied()
+                .unwrap_or(0.0)
+        });
+    }
+
+    fn set_photosynthesis_rates(&mut self, rate: impl Fn(&str) -> f32) {
+        for species in self.species.iter_mut() {
+            species.photosynthesis_rate_s = rate(&species.name);
+        }
+        let species = self.species.clone();
+        self.apply_species(&species);
     }
 
     fn simulate_organism(
         organism: &mut Organism,
         delta: Duration,
         environment_awareness: &EnvironmentAwareness,
+        nutrients: &mut NutrientField,
     ) -> OrganismsChange {
-        let result = organism.simulate(delta, environment_awareness);
+        let result = organism.simulate(delta, environment_awareness, nutrients);
         match result {
             OrganismResult::HadChildren { amount }
                 if Self::can_add_children(organism, environment_awareness) =>
@@ -152,7 +546,7 @@ impl Simulation {
     fn create_organism_children(amount: u64, organism: &Organism) -> Vec<Organism> {
         let mut vec = Vec::new();
 
-        let angle = rand::random::<f32>() * std::f32::consts::TAU;
+        let angle = random::rng().gen::<f32>() * std::f32::consts::TAU;
         let angle_increase = std::f32::consts::TAU / amount as f32;
         for i in 0..amount {
             let away_vector =
@@ -182,12 +576,15 @@ impl Simulation {
         }
     }
 
-    fn generate_organisms(generation_configuration: &GenerationConfiguration) -> Vec<Organism> {
+    fn generate_organisms(
+        generation_configuration: &GenerationConfiguration,
+        terrain: &Terrain,
+    ) -> Vec<Organism> {
         let mut organisms = Vec::new();
 
         let amount_multiplier = 0.1f32;
 
-        let mut rng = rand::thread_rng();
+        let mut rng = random::rng();
         let coordinate_uniform = Uniform::new_inclusive(
             -BOUNDARY_DISTANCE_FROM_CENTER,
             BOUNDARY_DISTANCE_FROM_CENTER,
@@ -200,10 +597,11 @@ impl Simulation {
             for _ in 0..organisms_amount {
                 let mut organism =
                     Organism::new_randomized(species_configuration.species.to_owned());
-                organism.set_position_x_y(
-                    coordinate_uniform.sample(&mut rng),
-                    coordinate_uniform.sample(&mut rng),
-                );
+                let position = passable_position(terrain, || Point2 {
+                    x: coordinate_uniform.sample(&mut rng),
+                    y: coordinate_uniform.sample(&mut rng),
+                });
+                organism.set_position_x_y(position.x, position.y);
                 organisms.push(organism);
             }
         }
@@ -212,9 +610,123 @@ impl Simulation {
     }
 }
 
+/// A position from `pick` that isn't impassable, or the last one picked if it
+/// doesn't come up with one after a few tries.
+fn passable_position(terrain: &Terrain, mut pick: impl FnMut() -> Point2<f32>) -> Point2<f32> {
+    let mut position = pick();
+    for _ in 0..MAX_PLACEMENT_TRIES {
+        if terrain.is_passable(position) {
+            break;
+        }
+        position = pick();
+    }
+    position
+}
+
 pub enum OrganismsChange {
     Add(Vec<Organism>),
     Remove(u64),
     AddRemove(Vec<Organism>, u64),
     None,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A simulation of the default species without any organisms.
+    fn empty_simulation() -> Simulation {
+        let mut config = crate::generate_default_generation_configuration();
+        for species_config in config.species.iter_mut() {
+            species_config.amount_per_meter = 0.0;
+        }
+        Simulation::new(&config)
+    }
+
+    fn event(time_ms: u64, action: ScenarioAction) -> ScenarioEvent {
+        ScenarioEvent {
+            time: Duration::from_millis(time_ms),
+            action,
+        }
+    }
+
+    fn change_max_energy(species: &str, time_ms: u64, value: f32) -> ScenarioEvent {
+        event(
+            time_ms,
+            ScenarioAction::ChangeParameter {
+                species: species.to_string(),
+                parameter: String::from("max_energy"),
+                value,
+            },
+        )
+    }
+
+    #[test]
+    fn steps_are_split_at_event_times() {
+        let mut simulation = empty_simulation();
+        let species = simulation.species[0].name.clone();
+        simulation.set_scenario(&Scenario {
+            events: vec![event(
+                300,
+                ScenarioAction::Introduce {
+                    species,
+                    amount: 1,
+                    center: [0.0, 0.0],
+                    radius: 0.0,
+                },
+            )],
+            seed: None,
+        });
+        simulation.run(Duration::from_secs(1));
+
+        // born at exactly 0.3s, so it lived for the rest of the step
+        assert_eq!(simulation.organisms.len(), 1);
+        assert_eq!(
+            simulation.organisms[0].shared_state().age(),
+            Duration::from_millis(700)
+        );
+        assert_eq!(simulation.time(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn events_at_the_same_time_keep_their_order() {
+        let mut simulation = empty_simulation();
+        let species = simulation.species[0].name.clone();
+        simulation.set_scenario(&Scenario {
+            events: vec![
+                change_max_energy(&species, 500, 10.0),
+                change_max_energy(&species, 200, 30.0),
+                change_max_energy(&species, 500, 20.0),
+            ],
+            seed: None,
+        });
+
+        simulation.run(Duration::from_millis(300));
+        assert_eq!(simulation.species[0].max_energy, 30.0);
+        simulation.run(Duration::from_secs(1));
+        assert_eq!(simulation.species[0].max_energy, 20.0);
+    }
+
+    #[test]
+    fn species_updates_during_a_drought_apply_after_it() {
+        let mut simulation = empty_simulation();
+        simulation.set_scenario(&Scenario {
+            events: vec![event(
+                0,
+                ScenarioAction::Drought {
+                    duration: Duration::from_secs(2),
+                },
+            )],
+            seed: None,
+        });
+        simulation.run(Duration::from_secs(1));
+
+        let mut species = simulation.species[0].clone();
+        species.photosynthesis_rate_s = 3.0;
+        simulation.update_species(&[species]);
+        assert_eq!(simulation.species[0].photosynthesis_rate_s, 0.0);
+
+        simulation.run(Duration::from_secs(2));
+        assert_eq!(simulation.species[0].photosynthesis_rate_s, 3.0);
+    }
+}
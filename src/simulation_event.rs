@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Something noteworthy that happened in a simulation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SimulationEvent {
+    pub time: Duration,
+    pub kind: SimulationEventKind,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum SimulationEventKind {
+    /// The last organism of a species died.
+    Extinction { species: String },
+    /// The whole population dropped to the collapse threshold.
+    Collapse,
+    /// The populations stopped changing.
+    SteadyState,
+    /// The populations keep repeating with this period.
+    LimitCycle { period: Duration },
+    /// Rain started, plants grow faster while it lasts.
+    Rain { duration: Duration },
+    /// A drought started, plants grow slower while it lasts.
+    Drought { duration: Duration },
+    /// A fire burned out after killing `casualties` organisms.
+    Fire { casualties: u32, burned_area: f32 },
+    /// A flood receded after drowning `casualties` organisms.
+    Flood { casualties: u32 },
+}
+
+impl SimulationEvent {
+    pub fn description(&self) -> String {
+        let what = match &self.kind {
+            SimulationEventKind::Extinction { species } => format!("{} went extinct", species),
+            SimulationEventKind::Collapse => String::from("population collapsed"),
+            SimulationEventKind::SteadyState => String::from("steady state reached"),
+            SimulationEventKind::LimitCycle { period } => {
+                format!("limit cycle of {:.1}s", period.as_secs_f32())
+            }
+            SimulationEventKind::Rain { duration } => {
+                format!("rain for {:.0}s", duration.as_secs_f32())
+            }
+            SimulationEventKind::Drought { duration } => {
+                format!("drought for {:.0}s", duration.as_secs_f32())
+            }
+            SimulationEventKind::Fire {
+                casualties,
+                burned_area,
+            } => format!("fire burned {:.0}m² and killed {}", burned_area, casualties),
+            SimulationEventKind::Flood { casualties } => {
+                format!("flood drowned {}", casualties)
+            }
+        };
+        format!("{:.1}s: {}", self.time.as_secs_f32(), what)
+    }
+}
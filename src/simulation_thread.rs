@@ -1,95 +1,400 @@
 use std::{
+    any::Any,
     collections::HashMap,
-    sync::mpsc::{self, Receiver, Sender},
-    thread,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
+use ggez::{
+    graphics::{Color, Rect},
+    mint::Point2,
+};
+
 use crate::{
-    configurations::generation_configuration::GenerationConfiguration,
-    organisms::organism_info::OrganismInfo, simulation::Simulation,
+    clock::ClockReading,
+    configurations::{generation_configuration::GenerationConfiguration, scenario::Scenario},
+    nutrient_field::NutrientField,
+    organisms::{organism_details::OrganismDetails, organism_info::OrganismInfo, species::Species},
+    random,
+    simulation::Simulation,
+    simulation_event::SimulationEvent,
+    terrain::Terrain,
+    triple_buffer::{self, TripleBufferReader, TripleBufferWriter},
+    weather::WeatherReading,
 };
 
 pub struct SimulationThread {
-    pub last_data: SimulationData,
-    organism_info_receiver: Receiver<SimulationData>,
+    snapshot_reader: TripleBufferReader<SimulationData>,
     message_sender: Sender<SimulationThreadMessage>,
+    event_receiver: Receiver<SimulationThreadEvent>,
+    join_handle: Option<JoinHandle<()>>,
+    panic: Option<SimulationPanic>,
+    running: bool,
+    organism_details: HashMap<u64, OrganismDetails>,
 }
 
 impl SimulationThread {
+    /// If `crash_dump_directory` is set, a snapshot of the simulation
+    /// is written there when the simulation panics.
+    /// The `scenario` starts over with every restart, with the same seed if it has one.
     pub fn new(
         initial_time_step: Duration,
         generation_configuration: GenerationConfiguration,
+        scenario: Scenario,
+        crash_dump_directory: Option<PathBuf>,
     ) -> Self {
-        let (organism_info_sender, organism_info_receiver) = mpsc::channel();
+        let (snapshot_writer, snapshot_reader) = triple_buffer::triple_buffer();
         let (message_sender, message_receiver) = mpsc::channel();
+        let (event_sender, event_receiver) = mpsc::channel();
 
-        thread::spawn(move || {
+        let join_handle = thread::spawn(move || {
+            if let Some(seed) = scenario.seed {
+                random::seed(seed);
+            }
             let mut simulation = Simulation::new(&generation_configuration);
-            let mut time_step = initial_time_step;
-            let mut target_time = Duration::ZERO;
+            simulation.set_scenario(&scenario);
+            let mut worker = SimulationWorker {
+                simulation,
+                scenario,
+                time_step: initial_time_step,
+                target_time: Duration::ZERO,
+                paused: false,
+                crashed: false,
+                snapshot_writer,
+                unpublished: true,
+                event_sender,
+                crash_dump_directory,
+            };
 
             loop {
-                if simulation.simulation_data.time < target_time {
-                    simulation.run(time_step);
-                    let send_result = organism_info_sender.send(simulation.simulation_data.clone());
-                    if let Err(error) = send_result {
-                        println!("Error sending simulation data to UI thread: {}", error);
-                        break;
-                    }
-                }
-
-                while let Ok(message) = message_receiver.try_recv() {
-                    match message {
-                        SimulationThreadMessage::AdvanceTo(new_target_time) => {
-                            target_time = new_target_time;
-                        }
-                        SimulationThreadMessage::ChangeTimeStep(new_time_step) => {
-                            time_step = new_time_step;
-                        }
-                        SimulationThreadMessage::Restart(new_generation_configuration) => {
-                            simulation = Simulation::new(&new_generation_configuration);
-                            target_time = Duration::ZERO;
+                let keep_running = if worker.has_work() {
+                    // there is work to do, so only look at messages without waiting
+                    match message_receiver.try_recv() {
+                        Ok(message) => worker.handle_message(message),
+                        Err(TryRecvError::Empty) => {
+                            worker.step();
+                            true
                         }
+                        Err(TryRecvError::Disconnected) => false,
+                    }
+                } else {
+                    // caught up or paused - make sure the UI has the final state,
+                    // then sleep until it tells us something
+                    worker.publish_snapshot();
+                    match message_receiver.recv() {
+                        Ok(message) => worker.handle_message(message),
+                        Err(_) => false,
                     }
+                };
+
+                if !keep_running {
+                    break;
                 }
             }
         });
 
         SimulationThread {
-            last_data: SimulationData {
-                organism_infos: Vec::new(),
-                organism_counter: HashMap::new(),
-                time: Duration::ZERO,
-                step: 0,
-            },
-            organism_info_receiver,
+            snapshot_reader,
             message_sender,
+            event_receiver,
+            join_handle: Some(join_handle),
+            panic: None,
+            running: true,
+            organism_details: HashMap::new(),
         }
     }
 
-    pub fn advance(&self, target_time: Duration) {
-        self.message_sender
-            .send(SimulationThreadMessage::AdvanceTo(target_time))
-            .unwrap();
+    pub fn advance(&mut self, target_time: Duration) {
+        self.send(SimulationThreadMessage::AdvanceTo(target_time));
     }
 
+    /// Picks up the newest snapshot and events published by the simulation, if any.
     pub fn probe(&mut self) {
-        while let Ok(data) = self.organism_info_receiver.try_recv() {
-            self.last_data = data;
+        self.snapshot_reader.update();
+
+        while let Ok(event) = self.event_receiver.try_recv() {
+            match event {
+                SimulationThreadEvent::Panicked(panic) => self.panic = Some(panic),
+                SimulationThreadEvent::OrganismDetails(id, Some(details)) => {
+                    self.organism_details.insert(id, details);
+                }
+                SimulationThreadEvent::OrganismDetails(id, None) => {
+                    self.organism_details.remove(&id);
+                }
+            }
+        }
+    }
+
+    pub fn last_data(&self) -> &SimulationData {
+        self.snapshot_reader.latest()
+    }
+
+    /// Asks the simulation for the details of an organism without stopping it.
+    /// The answer becomes available through [`SimulationThread::organism_details`]
+    /// after a later [`SimulationThread::probe`].
+    pub fn request_organism_details(&mut self, id: u64) {
+        self.send(SimulationThreadMessage::QueryOrganism(id));
+    }
+
+    /// The latest answer to [`SimulationThread::request_organism_details`].
+    /// None if it wasn't answered yet or the organism doesn't exist anymore.
+    pub fn organism_details(&self, id: u64) -> Option<&OrganismDetails> {
+        self.organism_details.get(&id)
+    }
+
+    /// Information about the last panic of the simulation,
+    /// cleared when the simulation is restarted.
+    pub fn panic(&self) -> Option<&SimulationPanic> {
+        self.panic.as_ref()
+    }
+
+    /// Whether the thread is still alive and accepting messages.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn change_time_step(&mut self, time_step: Duration) {
+        self.send(SimulationThreadMessage::ChangeTimeStep(time_step));
+    }
+
+    pub fn restart(&mut self, species_gen_config: GenerationConfiguration) {
+        self.panic = None;
+        self.organism_details.clear();
+        self.send(SimulationThreadMessage::Restart(Box::new(
+            species_gen_config,
+        )));
+    }
+
+    /// Changes the parameters of existing organisms without restarting.
+    pub fn update_species(&mut self, species: Vec<Species>) {
+        self.send(SimulationThreadMessage::UpdateSpecies(species));
+    }
+
+    /// Adds `amount` organisms of the configured species within `radius` of `center`.
+    pub fn spawn_organisms(
+        &mut self,
+        species_name: String,
+        center: Point2<f32>,
+        radius: f32,
+        amount: u32,
+    ) {
+        self.send(SimulationThreadMessage::SpawnOrganisms {
+            species_name,
+            center,
+            radius,
+            amount,
+        });
+    }
+
+    pub fn remove_organisms(&mut self, center: Point2<f32>, radius: f32) {
+        self.send(SimulationThreadMessage::RemoveOrganisms { center, radius });
+    }
+
+    pub fn pause(&mut self) {
+        self.send(SimulationThreadMessage::Pause);
+    }
+
+    pub fn resume(&mut self) {
+        self.send(SimulationThreadMessage::Resume);
+    }
+
+    pub fn single_step(&mut self, steps: u32) {
+        self.send(SimulationThreadMessage::SingleStep(steps));
+    }
+
+    /// Stops the simulation thread and waits for it to finish.
+    pub fn shutdown(&mut self) {
+        self.send(SimulationThreadMessage::Shutdown);
+        if let Some(join_handle) = self.join_handle.take() {
+            if join_handle.join().is_err() {
+                println!("Simulation thread terminated with a panic.");
+            }
+        }
+        self.running = false;
+    }
+
+    fn send(&mut self, message: SimulationThreadMessage) {
+        if self.message_sender.send(message).is_err() {
+            // the thread is gone - the reason, if any, arrives as an event
+            self.running = false;
+        }
+    }
+}
+
+impl Drop for SimulationThread {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+struct SimulationWorker {
+    simulation: Simulation,
+    scenario: Scenario,
+    time_step: Duration,
+    target_time: Duration,
+    paused: bool,
+    /// Set when the simulation panicked. Only a restart gets it going again.
+    crashed: bool,
+    snapshot_writer: TripleBufferWriter<SimulationData>,
+    /// Whether the simulation advanced since the last published snapshot.
+    unpublished: bool,
+    event_sender: Sender<SimulationThreadEvent>,
+    crash_dump_directory: Option<PathBuf>,
+}
+
+impl SimulationWorker {
+    fn has_work(&self) -> bool {
+        !self.paused && !self.crashed && self.simulation.time() < self.target_time
+    }
+
+    /// Runs a single simulation step. Its result is only copied out
+    /// if the UI has already taken the previous snapshot.
+    fn step(&mut self) {
+        if self.crashed {
+            return;
         }
+
+        let time_step = self.time_step;
+        let simulation = &mut self.simulation;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| simulation.run(time_step)));
+        if let Err(payload) = result {
+            self.on_panic(payload);
+            return;
+        }
+
+        self.unpublished = true;
+        if self.snapshot_writer.is_consumed() {
+            self.publish_snapshot();
+        }
+    }
+
+    fn publish_snapshot(&mut self) {
+        if !self.unpublished || self.crashed {
+            return;
+        }
+        self.simulation
+            .write_snapshot(self.snapshot_writer.back_mut());
+        self.snapshot_writer.publish();
+        self.unpublished = false;
     }
 
-    pub fn change_time_step(&self, time_step: Duration) {
-        self.message_sender
-            .send(SimulationThreadMessage::ChangeTimeStep(time_step))
-            .unwrap();
+    fn on_panic(&mut self, payload: Box<dyn Any + Send>) {
+        self.crashed = true;
+
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("unknown panic")
+        };
+
+        let snapshot_dump = self.dump_snapshot();
+
+        // if the UI is gone there's no one to tell
+        let _ = self
+            .event_sender
+            .send(SimulationThreadEvent::Panicked(SimulationPanic {
+                step: self.simulation.step(),
+                time: self.simulation.time(),
+                message,
+                snapshot_dump,
+            }));
     }
 
-    pub fn restart(&self, species_gen_config: GenerationConfiguration) {
-        self.message_sender
-            .send(SimulationThreadMessage::Restart(species_gen_config))
-            .unwrap();
+    /// Writes the state the simulation was left in after a panic.
+    /// The state may be inconsistent, so writing it may fail as well.
+    fn dump_snapshot(&self) -> Option<PathBuf> {
+        let directory = self.crash_dump_directory.as_ref()?;
+
+        let simulation = &self.simulation;
+        let json = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut snapshot = SimulationData::default();
+            simulation.write_snapshot(&mut snapshot);
+            snapshot.to_json()
+        }))
+        .ok()?;
+
+        let path = directory.join(format!("crash_step_{}.json", simulation.step()));
+        let write_result = fs::create_dir_all(directory).and_then(|_| fs::write(&path, json));
+        if let Err(error) = write_result {
+            println!("Writing crash dump failed: {}", error);
+            return None;
+        }
+        Some(path)
+    }
+
+    /// Returns false if the thread should stop.
+    fn handle_message(&mut self, message: SimulationThreadMessage) -> bool {
+        match message {
+            SimulationThreadMessage::AdvanceTo(new_target_time) => {
+                self.target_time = new_target_time;
+            }
+            SimulationThreadMessage::ChangeTimeStep(new_time_step) => {
+                self.time_step = new_time_step;
+            }
+            SimulationThreadMessage::Restart(new_generation_configuration) => {
+                if let Some(seed) = self.scenario.seed {
+                    random::seed(seed);
+                }
+                let result = panic::catch_unwind(|| Simulation::new(&new_generation_configuration));
+                match result {
+                    Ok(simulation) => {
+                        self.simulation = simulation;
+                        self.simulation.set_scenario(&self.scenario);
+                        self.crashed = false;
+                        self.target_time = Duration::ZERO;
+                        self.unpublished = true;
+                    }
+                    Err(payload) => self.on_panic(payload),
+                }
+            }
+            SimulationThreadMessage::UpdateSpecies(species) => {
+                self.simulation.update_species(&species);
+                self.unpublished = true;
+            }
+            SimulationThreadMessage::SpawnOrganisms {
+                species_name,
+                center,
+                radius,
+                amount,
+            } => {
+                self.simulation
+                    .spawn_organisms(&species_name, center, radius, amount);
+                self.unpublished = true;
+            }
+            SimulationThreadMessage::RemoveOrganisms { center, radius } => {
+                self.simulation.remove_organisms(center, radius);
+                self.unpublished = true;
+            }
+            SimulationThreadMessage::Pause => self.paused = true,
+            SimulationThreadMessage::Resume => {
+                // don't try to catch up with the time requested while paused
+                self.target_time = self.simulation.time();
+                self.paused = false;
+            }
+            SimulationThreadMessage::SingleStep(steps) => {
+                for _ in 0..steps {
+                    self.step();
+                }
+                self.target_time = self.target_time.max(self.simulation.time());
+            }
+            SimulationThreadMessage::QueryOrganism(id) => {
+                let details = self.simulation.organism_details(id);
+                let _ = self
+                    .event_sender
+                    .send(SimulationThreadEvent::OrganismDetails(id, details));
+            }
+            SimulationThreadMessage::Shutdown => return false,
+        }
+        true
     }
 }
 
@@ -97,12 +402,86 @@ impl SimulationThread {
 pub struct SimulationData {
     pub organism_infos: Vec<OrganismInfo>,
     pub organism_counter: HashMap<String, u32>,
+    /// The configured species in their configured order, the ones that can be spawned.
+    pub species_names: Vec<String>,
+    pub species_colors: HashMap<String, Color>,
+    /// World rectangle of every chunk the simulation knows about
+    /// and how many organisms were in it at the start of the last step.
+    pub chunk_occupancy: Vec<(Rect, usize)>,
+    pub pending_scenario_events: usize,
+    pub events: Vec<SimulationEvent>,
+    pub terrain: Arc<Terrain>,
+    pub nutrients: NutrientField,
+    /// None when the clock is turned off.
+    pub clock: Option<ClockReading>,
+    pub weather: WeatherReading,
     pub time: Duration,
     pub step: u64,
 }
 
+impl SimulationData {
+    pub fn to_json(&self) -> String {
+        let organisms: Vec<serde_json::Value> = self
+            .organism_infos
+            .iter()
+            .map(|info| {
+                let shared_state = info.shared_state();
+                serde_json::json!({
+                    "id": info.id(),
+                    "species": shared_state.species.name,
+                    "x": shared_state.position.x,
+                    "y": shared_state.position.y,
+                    "energy": shared_state.energy(),
+                    "health": shared_state.health,
+                    "age_s": shared_state.age().as_secs_f32(),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "step": self.step,
+            "time_s": self.time.as_secs_f32(),
+            "organism_counter": self.organism_counter,
+            "events": self.events,
+            "organisms": organisms,
+        })
+        .to_string()
+    }
+}
+
+pub struct SimulationPanic {
+    pub step: u64,
+    pub time: Duration,
+    pub message: String,
+    /// Where the state of the simulation was written, if it was.
+    pub snapshot_dump: Option<PathBuf>,
+}
+
+enum SimulationThreadEvent {
+    Panicked(SimulationPanic),
+    OrganismDetails(u64, Option<OrganismDetails>),
+}
+
 enum SimulationThreadMessage {
     AdvanceTo(Duration),
     ChangeTimeStep(Duration),
-    Restart(GenerationConfiguration),
+    Restart(Box<GenerationConfiguration>),
+    UpdateSpecies(Vec<Species>),
+    SpawnOrganisms {
+        species_name: String,
+        center: Point2<f32>,
+        radius: f32,
+        amount: u32,
+    },
+    RemoveOrganisms {
+        center: Point2<f32>,
+        radius: f32,
+    },
+    /// Stops advancing until [`SimulationThreadMessage::Resume`], ignoring `AdvanceTo`.
+    Pause,
+    Resume,
+    /// Runs the given amount of steps immediately, even when paused.
+    SingleStep(u32),
+    QueryOrganism(u64),
+    Shutdown,
 }
@@ -0,0 +1,275 @@
+use ggez::{graphics::Rect, mint::Point2};
+
+use crate::{
+    configurations::terrain_configuration::{
+        Biome, BiomeProperties, BiomeTable, Obstacle, TerrainConfiguration, TerrainSource,
+    },
+    simulation::BOUNDARY_DISTANCE_FROM_CENTER,
+};
+
+/// Procedural terrain lower than this is water.
+const WATER_LEVEL: f32 = 0.38;
+/// Procedural terrain drier than this is desert, wetter than this is forest.
+const DESERT_MOISTURE: f32 = 0.42;
+const FOREST_MOISTURE: f32 = 0.58;
+const NOISE_OCTAVES: u32 = 4;
+/// The elevation of flat terrain and of everything outside of the world.
+const FLAT_ELEVATION: f32 = 0.5;
+
+/// A grid of biomes covering the whole world, and the obstacles on it.
+/// Outside of the world it's grassland.
+pub struct Terrain {
+    width: usize,
+    height: usize,
+    /// Row by row, starting at the top left.
+    cells: Vec<Biome>,
+    /// How high every cell is, from 0 to 1, in the same order as `cells`.
+    elevations: Vec<f32>,
+    biomes: BiomeTable,
+    obstacles: Vec<Obstacle>,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Self::flat(&TerrainConfiguration::default())
+    }
+}
+
+impl Terrain {
+    pub fn new(config: &TerrainConfiguration) -> Result<Self, String> {
+        let mut terrain = Self::biomes_from(config)?;
+        terrain.obstacles = config.obstacles.clone();
+        Ok(terrain)
+    }
+
+    /// Grassland everywhere, with the obstacles of `config`.
+    pub fn flat(config: &TerrainConfiguration) -> Self {
+        Self {
+            width: 1,
+            height: 1,
+            cells: vec![Biome::Grassland],
+            elevations: vec![FLAT_ELEVATION],
+            biomes: config.biomes.clone(),
+            obstacles: config.obstacles.clone(),
+        }
+    }
+
+    fn biomes_from(config: &TerrainConfiguration) -> Result<Self, String> {
+        match &config.source {
+            TerrainSource::Flat => Ok(Self::flat(config)),
+            TerrainSource::Image { path } => {
+                let image = image::open(path)
+                    .map_err(|error| format!("reading {} failed: {}", path.display(), error))?
+                    .to_luma8();
+                if image.width() == 0 || image.height() == 0 {
+                    return Err(format!("{} is empty", path.display()));
+                }
+                let cells = image
+                    .pixels()
+                    .map(|pixel| match pixel.0[0] {
+                        0..=63 => Biome::Water,
+                        64..=127 => Biome::Grassland,
+                        128..=191 => Biome::Forest,
+                        _ => Biome::Desert,
+                    })
+                    .collect();
+                // brighter is higher, like the order of the biomes
+                let elevations = image
+                    .pixels()
+                    .map(|pixel| pixel.0[0] as f32 / 255.0)
+                    .collect();
+                Ok(Self {
+                    width: image.width() as usize,
+                    height: image.height() as usize,
+                    cells,
+                    elevations,
+                    biomes: config.biomes.clone(),
+                    obstacles: Vec::new(),
+                })
+            }
+            TerrainSource::Procedural {
+                seed,
+                cell_size,
+                feature_size,
+            } => {
+                let size =
+                    ((2.0 * BOUNDARY_DISTANCE_FROM_CENTER / cell_size).ceil() as usize).max(1);
+                let mut terrain = Self {
+                    width: size,
+                    height: size,
+                    cells: Vec::with_capacity(size * size),
+                    elevations: Vec::with_capacity(size * size),
+                    biomes: config.biomes.clone(),
+                    obstacles: Vec::new(),
+                };
+                for y in 0..size {
+                    for x in 0..size {
+                        let center = terrain.cell_rect(x, y).center();
+                        let x = center.x / feature_size;
+                        let y = center.y / feature_size;
+                        let elevation = fractal_noise(*seed, x, y);
+                        let moisture = fractal_noise(seed.wrapping_add(1), x, y);
+                        terrain.elevations.push(elevation);
+                        terrain.cells.push(if elevation < WATER_LEVEL {
+                            Biome::Water
+                        } else if moisture < DESERT_MOISTURE {
+                            Biome::Desert
+                        } else if moisture > FOREST_MOISTURE {
+                            Biome::Forest
+                        } else {
+                            Biome::Grassland
+                        });
+                    }
+                }
+                Ok(terrain)
+            }
+        }
+    }
+
+    pub fn biome_at(&self, point: Point2<f32>) -> Biome {
+        self.cell_index(point)
+            .map_or(Biome::Grassland, |index| self.cells[index])
+    }
+
+    /// How high the ground at `point` is, from 0 to 1.
+    pub fn elevation_at(&self, point: Point2<f32>) -> f32 {
+        self.cell_index(point)
+            .map_or(FLAT_ELEVATION, |index| self.elevations[index])
+    }
+
+    /// None outside of the world.
+    fn cell_index(&self, point: Point2<f32>) -> Option<usize> {
+        let world_size = 2.0 * BOUNDARY_DISTANCE_FROM_CENTER;
+        let fraction_x = (point.x + BOUNDARY_DISTANCE_FROM_CENTER) / world_size;
+        let fraction_y = (point.y + BOUNDARY_DISTANCE_FROM_CENTER) / world_size;
+        if !(0.0..=1.0).contains(&fraction_x) || !(0.0..=1.0).contains(&fraction_y) {
+            return None;
+        }
+        let x = ((fraction_x * self.width as f32) as usize).min(self.width - 1);
+        let y = ((fraction_y * self.height as f32) as usize).min(self.height - 1);
+        Some(y * self.width + x)
+    }
+
+    pub fn properties_at(&self, point: Point2<f32>) -> &BiomeProperties {
+        self.biomes.get(self.biome_at(point))
+    }
+
+    pub fn is_passable(&self, point: Point2<f32>) -> bool {
+        !self.properties_at(point).impassable
+            && !self
+                .obstacles
+                .iter()
+                .any(|obstacle| obstacle.contains(point))
+    }
+
+    /// Whether an obstacle is between `from` and `to`.
+    pub fn blocks_sight(&self, from: Point2<f32>, to: Point2<f32>) -> bool {
+        self.obstacles
+            .iter()
+            .any(|obstacle| obstacle.intersects_segment(from, to))
+    }
+
+    /// Whether organisms can walk straight everywhere.
+    pub fn is_open(&self) -> bool {
+        self.obstacles.is_empty()
+            && !Biome::ALL
+                .into_iter()
+                .any(|biome| self.biomes.get(biome).impassable && self.cells.contains(&biome))
+    }
+
+    /// Whether everything is grassland, so there are no biomes to draw.
+    pub fn is_flat(&self) -> bool {
+        self.cells.iter().all(|biome| *biome == Biome::Grassland)
+    }
+
+    pub fn obstacles(&self) -> &[Obstacle] {
+        &self.obstacles
+    }
+
+    /// Every cell with its rectangle in the world and its elevation.
+    pub fn elevations(&self) -> impl Iterator<Item = (Rect, f32)> + '_ {
+        self.elevations
+            .iter()
+            .enumerate()
+            .map(|(index, elevation)| {
+                (
+                    self.cell_rect(index % self.width, index / self.width),
+                    *elevation,
+                )
+            })
+    }
+
+    /// Every cell with its rectangle in the world.
+    pub fn cells(&self) -> impl Iterator<Item = (Rect, Biome)> + '_ {
+        self.cells.iter().enumerate().map(|(index, biome)| {
+            (
+                self.cell_rect(index % self.width, index / self.width),
+                *biome,
+            )
+        })
+    }
+
+    fn cell_rect(&self, x: usize, y: usize) -> Rect {
+        let world_size = 2.0 * BOUNDARY_DISTANCE_FROM_CENTER;
+        let cell_width = world_size / self.width as f32;
+        let cell_height = world_size / self.height as f32;
+        Rect::new(
+            x as f32 * cell_width - BOUNDARY_DISTANCE_FROM_CENTER,
+            y as f32 * cell_height - BOUNDARY_DISTANCE_FROM_CENTER,
+            cell_width,
+            cell_height,
+        )
+    }
+}
+
+/// Smooth noise from 0 to 1 with details of several sizes, the biggest about 1 unit.
+fn fractal_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = 1.0;
+    for octave in 0..NOISE_OCTAVES {
+        sum += amplitude
+            * value_noise(
+                seed.wrapping_add(octave as u64 * 7919),
+                x * frequency,
+                y * frequency,
+            );
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total_amplitude
+}
+
+/// Random values at integer coordinates, smoothly interpolated in between.
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let top = lerp(
+        lattice_value(seed, x0, y0),
+        lattice_value(seed, x0 + 1, y0),
+        tx,
+    );
+    let bottom = lerp(
+        lattice_value(seed, x0, y0 + 1),
+        lattice_value(seed, x0 + 1, y0 + 1),
+        tx,
+    );
+    lerp(top, bottom, ty)
+}
+
+/// A random value from 0 to 1 for a point of the lattice, always the same for the same seed.
+fn lattice_value(seed: u64, x: i64, y: i64) -> f32 {
+    // splitmix64 of the seed and the coordinates
+    let mut hash = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 31;
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
@@ -0,0 +1,118 @@
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+struct SharedBuffer<T> {
+    middle: Mutex<T>,
+    fresh: AtomicBool,
+}
+
+pub struct TripleBufferWriter<T> {
+    back: T,
+    shared: Arc<SharedBuffer<T>>,
+}
+
+pub struct TripleBufferReader<T> {
+    front: T,
+    shared: Arc<SharedBuffer<T>>,
+}
+
+/// Hands the latest value from one thread to another without queueing.
+///
+/// The writer fills its back buffer and publishes it by swapping it with the
+/// shared middle buffer. The reader swaps the middle buffer into its front
+/// buffer when something new was published. Buffers are only ever swapped,
+/// never reallocated, so the writer can reuse the allocations of old values.
+pub fn triple_buffer<T: Default>() -> (TripleBufferWriter<T>, TripleBufferReader<T>) {
+    let shared = Arc::new(SharedBuffer {
+        middle: Mutex::new(T::default()),
+        fresh: AtomicBool::new(false),
+    });
+
+    (
+        TripleBufferWriter {
+            back: T::default(),
+            shared: shared.clone(),
+        },
+        TripleBufferReader {
+            front: T::default(),
+            shared,
+        },
+    )
+}
+
+impl<T> TripleBufferWriter<T> {
+    /// Whether the reader has taken the last published value.
+    pub fn is_consumed(&self) -> bool {
+        !self.shared.fresh.load(Ordering::Acquire)
+    }
+
+    /// The buffer to fill before calling [`TripleBufferWriter::publish`].
+    /// It holds an older value, so it can be overwritten in place.
+    pub fn back_mut(&mut self) -> &mut T {
+        &mut self.back
+    }
+
+    pub fn publish(&mut self) {
+        let mut middle = self.shared.middle.lock().unwrap_or_else(|e| e.into_inner());
+        mem::swap(&mut self.back, &mut *middle);
+        // set while the lock is held, so the reader never sees the flag without the value
+        self.shared.fresh.store(true, Ordering::Release);
+    }
+}
+
+impl<T> TripleBufferReader<T> {
+    /// Takes the latest published value, if there is one.
+    /// Returns whether the front buffer changed.
+    pub fn update(&mut self) -> bool {
+        if self.shared.fresh.load(Ordering::Acquire) {
+            // the flag is only cleared under the lock, otherwise a publish in between
+            // would leave the reader with an older value than the one it already has
+            let mut middle = self.shared.middle.lock().unwrap_or_else(|e| e.into_inner());
+            if self.shared.fresh.swap(false, Ordering::AcqRel) {
+                mem::swap(&mut self.front, &mut *middle);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn latest(&self) -> &T {
+        &self.front
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn reader_never_goes_backwards() {
+        const VALUES: u64 = 100_000;
+        let (mut writer, mut reader) = triple_buffer::<u64>();
+
+        let writer_thread = thread::spawn(move || {
+            for value in 1..=VALUES {
+                *writer.back_mut() = value;
+                writer.publish();
+            }
+        });
+
+        // the last value stays published until it's read, so this ends
+        let mut last = 0;
+        while last < VALUES {
+            if reader.update() {
+                let value = *reader.latest();
+                assert!(value > last, "read {} after {}", value, last);
+                last = value;
+            }
+        }
+        writer_thread.join().unwrap();
+    }
+}
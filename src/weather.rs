@@ -0,0 +1,333 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    time::Duration,
+};
+
+use ggez::{graphics::Rect, mint::Point2};
+use rand::{seq::IteratorRandom, Rng};
+
+use crate::{
+    configurations::weather_configuration::WeatherConfiguration,
+    organisms::{organism::Organism, species::Nutrition},
+    random,
+    simulation_event::SimulationEventKind,
+    terrain::Terrain,
+};
+
+type FireCell = (i32, i32);
+
+/// Starts rains, droughts, fires and floods at random and runs them.
+pub struct Weather {
+    config: WeatherConfiguration,
+    spell: Option<Spell>,
+    fire: Option<Fire>,
+    flood: Option<Flood>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpellKind {
+    Rain,
+    Drought,
+}
+
+struct Spell {
+    kind: SpellKind,
+    until: Duration,
+    photosynthesis: f32,
+}
+
+struct Fire {
+    /// The cells on fire and how much longer they burn.
+    /// Ordered, so that the same seed spreads the same way.
+    burning: BTreeMap<FireCell, Duration>,
+    burned_out: BTreeSet<FireCell>,
+    casualties: u32,
+}
+
+struct Flood {
+    until: Duration,
+    casualties: u32,
+}
+
+/// What the weather did during a step.
+#[derive(Default)]
+pub struct WeatherOutcome {
+    /// The ids of the organisms it killed.
+    pub killed: Vec<u64>,
+    pub events: Vec<SimulationEventKind>,
+}
+
+/// The weather at one moment, for drawing.
+#[derive(Clone, Default)]
+pub struct WeatherReading {
+    /// The rain or drought and how much longer it lasts.
+    pub spell: Option<(SpellKind, Duration)>,
+    /// The world rectangles that are on fire.
+    pub burning: Vec<Rect>,
+    /// The water level of the flood, if there is one.
+    pub flood_level: Option<f32>,
+}
+
+impl Weather {
+    pub fn new(config: &WeatherConfiguration) -> Self {
+        Self {
+            config: config.clone(),
+            spell: None,
+            fire: None,
+            flood: None,
+        }
+    }
+
+    /// What photosynthesis is multiplied by right now.
+    pub fn photosynthesis(&self) -> f32 {
+        self.spell
+            .as_ref()
+            .map_or(1.0, |spell| spell.photosynthesis)
+    }
+
+    /// Ends what is over, starts new weather by chance and lets fires and floods
+    /// kill organisms. `time` is the time at the end of the step.
+    /// Organisms in `removed` are gone already and can't be killed.
+    pub fn step(
+        &mut self,
+        time: Duration,
+        delta: Duration,
+        organisms: &[Organism],
+        removed: &HashSet<u64>,
+        terrain: &Terrain,
+    ) -> WeatherOutcome {
+        let mut outcome = WeatherOutcome::default();
+        self.step_spell(time, delta, &mut outcome);
+        self.step_fire(delta, organisms, removed, &mut outcome);
+        self.step_flood(time, delta, organisms, removed, terrain, &mut outcome);
+        outcome
+    }
+
+    pub fn reading(&self, time: Duration) -> WeatherReading {
+        let cell_size = self.config.fire.cell_size;
+        WeatherReading {
+            spell: self
+                .spell
+                .as_ref()
+                .map(|spell| (spell.kind, spell.until.saturating_sub(time))),
+            burning: self
+                .fire
+                .iter()
+                .flat_map(|fire| fire.burning.keys())
+                .map(|&(x, y)| {
+                    Rect::new(
+                        x as f32 * cell_size,
+                        y as f32 * cell_size,
+                        cell_size,
+                        cell_size,
+                    )
+                })
+                .collect(),
+            flood_level: self.flood.as_ref().map(|_| self.config.flood.water_level),
+        }
+    }
+
+    fn step_spell(&mut self, time: Duration, delta: Duration, outcome: &mut WeatherOutcome) {
+        if self.spell.as_ref().is_some_and(|spell| spell.until <= time) {
+            self.spell = None;
+        }
+        if self.spell.is_some() {
+            return;
+        }
+
+        let spells = [
+            (SpellKind::Rain, &self.config.rain),
+            (SpellKind::Drought, &self.config.drought),
+        ];
+        for (kind, config) in spells {
+            if starts(config.mean_interval, delta) {
+                self.spell = Some(Spell {
+                    kind,
+                    until: time + config.duration,
+                    photosynthesis: config.photosynthesis,
+                });
+                outcome.events.push(match kind {
+                    SpellKind::Rain => SimulationEventKind::Rain {
+                        duration: config.duration,
+                    },
+                    SpellKind::Drought => SimulationEventKind::Drought {
+                        duration: config.duration,
+                    },
+                });
+                return;
+            }
+        }
+    }
+
+    fn step_fire(
+        &mut self,
+        delta: Duration,
+        organisms: &[Organism],
+        removed: &HashSet<u64>,
+        outcome: &mut WeatherOutcome,
+    ) {
+        let config = &self.config.fire;
+        if self.fire.is_none() && !starts(config.mean_interval, delta) {
+            return;
+        }
+
+        let cell_of = |position: Point2<f32>| -> FireCell {
+            (
+                (position.x / config.cell_size).floor() as i32,
+                (position.y / config.cell_size).floor() as i32,
+            )
+        };
+        let mut plants: HashMap<FireCell, u32> = HashMap::new();
+        for organism in organisms.iter().filter(|organism| is_plant(organism)) {
+            *plants.entry(cell_of(organism.position())).or_default() += 1;
+        }
+        let min_plants = config.min_plant_density * config.cell_size * config.cell_size;
+        let can_burn =
+            |cell: &FireCell| plants.get(cell).copied().unwrap_or(0) as f32 >= min_plants;
+
+        let fire = match self.fire.as_mut() {
+            Some(fire) => fire,
+            None => {
+                // lightning strikes a random plant, which only spreads in dense enough plants
+                let Some(struck) = organisms
+                    .iter()
+                    .filter(|organism| is_plant(organism))
+                    .choose(&mut random::rng())
+                else {
+                    return;
+                };
+                let cell = cell_of(struck.position());
+                if !can_burn(&cell) {
+                    return;
+                }
+                self.fire.insert(Fire {
+                    burning: BTreeMap::from([(cell, config.burn_duration)]),
+                    burned_out: BTreeSet::new(),
+                    casualties: 0,
+                })
+            }
+        };
+
+        let spread_chance = (config.spread_chance_s * delta.as_secs_f32()).min(1.0) as f64;
+        let mut ignited = BTreeSet::new();
+        for &(x, y) in fire.burning.keys() {
+            for neighbour in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                if !fire.burning.contains_key(&neighbour)
+                    && !fire.burned_out.contains(&neighbour)
+                    && can_burn(&neighbour)
+                    && random::rng().gen_bool(spread_chance)
+                {
+                    ignited.insert(neighbour);
+                }
+            }
+        }
+
+        let kill_chance = (config.kill_chance_s * delta.as_secs_f32()).min(1.0) as f64;
+        for organism in organisms {
+            if can_die(organism, removed, outcome)
+                && fire.burning.contains_key(&cell_of(organism.position()))
+                && random::rng().gen_bool(kill_chance)
+            {
+                outcome.killed.push(organism.id());
+                fire.casualties += 1;
+            }
+        }
+
+        for time_left in fire.burning.values_mut() {
+            *time_left = time_left.saturating_sub(delta);
+        }
+        let burned_out: Vec<FireCell> = fire
+            .burning
+            .iter()
+            .filter(|(_, time_left)| time_left.is_zero())
+            .map(|(cell, _)| *cell)
+            .collect();
+        for cell in burned_out {
+            fire.burning.remove(&cell);
+            fire.burned_out.insert(cell);
+        }
+        for cell in ignited {
+            fire.burning.insert(cell, config.burn_duration);
+        }
+
+        if fire.burning.is_empty() {
+            outcome.events.push(SimulationEventKind::Fire {
+                casualties: fire.casualties,
+                burned_area: fire.burned_out.len() as f32 * config.cell_size * config.cell_size,
+            });
+            self.fire = None;
+        }
+    }
+
+    fn step_flood(
+        &mut self,
+        time: Duration,
+        delta: Duration,
+        organisms: &[Organism],
+        removed: &HashSet<u64>,
+        terrain: &Terrain,
+        outcome: &mut WeatherOutcome,
+    ) {
+        let config = &self.config.flood;
+        if self.flood.is_none() {
+            // there is no point in a flood without low terrain
+            if !starts(config.mean_interval, delta)
+                || !terrain
+                    .elevations()
+                    .any(|(_, elevation)| elevation < config.water_level)
+            {
+                return;
+            }
+            self.flood = Some(Flood {
+                until: time + config.duration,
+                casualties: 0,
+            });
+        }
+        let Some(flood) = self.flood.as_mut() else {
+            return;
+        };
+
+        let drown_chance = (config.drown_chance_s * delta.as_secs_f32()).min(1.0) as f64;
+        for organism in organisms {
+            if can_die(organism, removed, outcome)
+                && terrain.elevation_at(organism.position()) < config.water_level
+                && random::rng().gen_bool(drown_chance)
+            {
+                outcome.killed.push(organism.id());
+                flood.casualties += 1;
+            }
+        }
+
+        if flood.until <= time {
+            outcome.events.push(SimulationEventKind::Flood {
+                casualties: flood.casualties,
+            });
+            self.flood = None;
+        }
+    }
+}
+
+/// Whether something that happens every `mean_interval` on average starts during `delta`.
+fn starts(mean_interval: Option<Duration>, delta: Duration) -> bool {
+    let Some(mean_interval) = mean_interval else {
+        return false;
+    };
+    let chance = 1.0 - (-delta.as_secs_f64() / mean_interval.as_secs_f64()).exp();
+    random::rng().gen_bool(chance.clamp(0.0, 1.0))
+}
+
+fn is_plant(organism: &Organism) -> bool {
+    organism.shared_state().species.contained_nutrition == Nutrition::Plant
+}
+
+fn is_corpse(organism: &Organism) -> bool {
+    organism.shared_state().species.contained_nutrition == Nutrition::Corpse
+}
+
+/// Whether `organism` is alive and wasn't removed or killed already during this step.
+fn can_die(organism: &Organism, removed: &HashSet<u64>, outcome: &WeatherOutcome) -> bool {
+    !is_corpse(organism)
+        && organism.is_alive()
+        && !removed.contains(&organism.id())
+        && !outcome.killed.contains(&organism.id())
+}